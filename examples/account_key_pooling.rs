@@ -60,15 +60,15 @@ async fn main() {
     println!("All transactions are successful");
     println!(
         "Transaction one public key: {}",
-        results[0].transaction.public_key
+        results[0].view().transaction.public_key
     );
     println!(
         "Transaction two public key: {}",
-        results[1].transaction.public_key
+        results[1].view().transaction.public_key
     );
     assert_ne!(
-        results[0].transaction.public_key,
-        results[1].transaction.public_key
+        results[0].view().transaction.public_key,
+        results[1].view().transaction.public_key
     );
 
     println!("All transactions are successful");
@@ -8,10 +8,12 @@ async fn main() {
             .with_api_key("potential api key".parse().unwrap())
             .with_retries(5),
     );
+    // Send the view request to the backup endpoint as well if the primary one is slow to respond
+    network.hedge_delay = Some(std::time::Duration::from_millis(500));
     // Query latest block
     let _block = Chain::block()
         .at(Reference::Optimistic)
-        .fetch_from_mainnet()
+        .fetch_from(&network)
         .await
         .unwrap();
 }
@@ -10,6 +10,7 @@ use url::Url;
 
 use crate::{
     common::send::Transactionable,
+    config::shared_client,
     errors::{AccountCreationError, FaucetError, ValidationError},
     transactions::{ConstructTransaction, TransactionWithSign},
     types::transactions::PrepopulateTransaction,
@@ -119,16 +120,23 @@ impl CreateAccountByFaucet {
             None => return Err(FaucetError::FaucetIsNotDefined(config.network_name.clone())),
         };
 
-        self.send_to_faucet(faucet_service_url).await
+        self.send_with_client(&config.http_client, faucet_service_url)
+            .await
     }
 
     pub async fn send_to_faucet(self, url: &Url) -> Result<Response, FaucetError> {
+        self.send_with_client(shared_client(), url).await
+    }
+
+    async fn send_with_client(
+        self,
+        client: &reqwest::Client,
+        url: &Url,
+    ) -> Result<Response, FaucetError> {
         let mut data = std::collections::HashMap::new();
         data.insert("newAccountId", self.new_account_id.to_string());
         data.insert("newAccountPublicKey", self.public_key.to_string());
 
-        let client = reqwest::Client::new();
-
         Ok(client.post(url.clone()).json(&data).send().await?)
     }
 }
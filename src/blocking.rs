@@ -0,0 +1,54 @@
+//! Blocking API for the synchronous code, enabled with the `blocking` feature.
+//!
+//! The builders get the `*_blocking` versions of their async methods, e.g. `fetch_from_blocking`
+//! and `send_to_blocking`. They run the future on the runtime that is shared by all the blocking calls,
+//! so the connections of the HTTP client stay alive between the calls.
+//!
+//! The blocking methods refuse to run inside the async runtime, as it would block its worker thread.
+
+use std::{future::Future, sync::OnceLock};
+
+use tokio::runtime::{Builder, Handle, Runtime};
+
+use crate::errors::BlockingError;
+
+/// The runtime threads only drive the I/O and timers, as the futures run on the calling thread.
+const RUNTIME_WORKER_THREADS: usize = 2;
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+fn runtime<E>() -> Result<&'static Runtime, BlockingError<E>> {
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = Builder::new_multi_thread()
+        .worker_threads(RUNTIME_WORKER_THREADS)
+        .thread_name("near-api-blocking")
+        .enable_all()
+        .build()?;
+    // Another thread could start the runtime first, then this one is dropped
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+/// Runs the future to completion on the shared runtime, blocking the current thread.
+pub fn block_on<T, E>(future: impl Future<Output = Result<T, E>>) -> Result<T, BlockingError<E>> {
+    if Handle::try_current().is_ok() {
+        return Err(BlockingError::InsideRuntime);
+    }
+    runtime()?.block_on(future).map_err(BlockingError::Error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_outside_of_the_runtime_only() {
+        let result = block_on(async { Ok::<_, ()>(1) });
+        assert!(matches!(result, Ok(1)));
+
+        let runtime = Builder::new_current_thread().build().unwrap();
+        let result = runtime.block_on(async { block_on(async { Ok::<_, ()>(1) }) });
+        assert!(matches!(result, Err(BlockingError::InsideRuntime)));
+    }
+}
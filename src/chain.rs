@@ -1,11 +1,18 @@
 use near_primitives::{
-    types::{BlockHeight, BlockReference},
-    views::BlockView,
+    types::{Balance, BlockHeight, BlockReference},
+    views::{BlockView, GasPriceView},
 };
 
 use crate::{
-    common::query::{BlockQueryBuilder, PostprocessHandler, RpcBlockHandler, SimpleBlockRpc},
-    types::CryptoHash,
+    common::query::{
+        BlockQueryBuilder, ChunkQueryBuilder, GasPriceQueryBuilder, GenesisConfigQueryBuilder,
+        NetworkInfoQueryBuilder, PostprocessHandler, ProtocolConfigQueryBuilder, RpcBlockHandler,
+        RpcChunkHandler, RpcGasPriceHandler, RpcGenesisConfigHandler, RpcNetworkInfoHandler,
+        RpcProtocolConfigHandler, RpcStatusHandler, SimpleBlockRpc, SimpleChunkRpc,
+        SimpleGasPriceRpc, SimpleGenesisConfigRpc, SimpleNetworkInfoRpc, SimpleProtocolConfigRpc,
+        SimpleStatusRpc, StatusQueryBuilder,
+    },
+    types::{reference::ChunkReference, CryptoHash},
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -37,4 +44,42 @@ impl Chain {
     pub fn block() -> BlockQueryBuilder<RpcBlockHandler> {
         BlockQueryBuilder::new(SimpleBlockRpc, BlockReference::latest(), RpcBlockHandler)
     }
+
+    pub fn chunk(reference: ChunkReference) -> ChunkQueryBuilder<RpcChunkHandler> {
+        ChunkQueryBuilder::new(SimpleChunkRpc, reference, RpcChunkHandler)
+    }
+
+    /// Status of the RPC node: its version, protocol version, latest block and validators.
+    pub fn status() -> StatusQueryBuilder<RpcStatusHandler> {
+        StatusQueryBuilder::new(SimpleStatusRpc, (), RpcStatusHandler)
+    }
+
+    pub fn protocol_config() -> ProtocolConfigQueryBuilder<RpcProtocolConfigHandler> {
+        ProtocolConfigQueryBuilder::new(
+            SimpleProtocolConfigRpc,
+            BlockReference::latest(),
+            RpcProtocolConfigHandler,
+        )
+    }
+
+    pub fn genesis_config() -> GenesisConfigQueryBuilder<RpcGenesisConfigHandler> {
+        GenesisConfigQueryBuilder::new(SimpleGenesisConfigRpc, (), RpcGenesisConfigHandler)
+    }
+
+    /// Gas price at the block, in yoctoNEAR per unit of gas.
+    /// The finality references return the gas price of the latest block.
+    pub fn gas_price() -> GasPriceQueryBuilder<PostprocessHandler<Balance, RpcGasPriceHandler>> {
+        GasPriceQueryBuilder::new(
+            SimpleGasPriceRpc,
+            BlockReference::latest(),
+            PostprocessHandler::new(
+                RpcGasPriceHandler,
+                Box::new(|data: GasPriceView| data.gas_price),
+            ),
+        )
+    }
+
+    pub fn network_info() -> NetworkInfoQueryBuilder<RpcNetworkInfoHandler> {
+        NetworkInfoQueryBuilder::new(SimpleNetworkInfoRpc, (), RpcNetworkInfoHandler)
+    }
 }
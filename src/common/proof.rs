@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, HashMap};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_primitives::{
+    hash::CryptoHash, state::ValueRef, trie_key::TrieKey, types::AccountId, views::ViewStateResult,
+};
+
+use crate::errors::StateProofError;
+
+/// Trie node as it's stored in the state and returned in the proof.
+#[derive(BorshSerialize, BorshDeserialize)]
+struct RawTrieNodeWithSize {
+    node: RawTrieNode,
+    memory_usage: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+enum RawTrieNode {
+    /// The rest of the key as the encoded nibbles, and the value.
+    Leaf(Vec<u8>, ValueRef),
+    BranchNoValue(Children),
+    BranchWithValue(ValueRef, Children),
+    /// The common part of the keys as the encoded nibbles, and the child.
+    Extension(Vec<u8>, CryptoHash),
+}
+
+/// Children of the branch node, one per nibble.
+/// It's serialized as the bitmap of the present children followed by their hashes.
+struct Children([Option<CryptoHash>; 16]);
+
+impl BorshSerialize for Children {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let bitmap = self
+            .0
+            .iter()
+            .enumerate()
+            .filter(|(_, child)| child.is_some())
+            .fold(0u16, |bitmap, (nibble, _)| bitmap | (1 << nibble));
+        bitmap.serialize(writer)?;
+        self.0
+            .iter()
+            .flatten()
+            .try_for_each(|hash| hash.serialize(writer))
+    }
+}
+
+impl BorshDeserialize for Children {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let bitmap = u16::deserialize_reader(reader)?;
+        let mut children = [None; 16];
+        for (nibble, child) in children.iter_mut().enumerate() {
+            if bitmap & (1 << nibble) != 0 {
+                *child = Some(CryptoHash::deserialize_reader(reader)?);
+            }
+        }
+        Ok(Self(children))
+    }
+}
+
+/// Decodes the key nibbles of the leaf or extension node.
+///
+/// The first byte holds the flags, and the first nibble if the number of nibbles is odd.
+#[allow(clippy::result_large_err)]
+fn decode_nibbles(encoded: &[u8]) -> Result<Vec<u8>, StateProofError> {
+    let (first, rest) = encoded.split_first().ok_or_else(|| {
+        StateProofError::InvalidProofNode(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "empty node key",
+        ))
+    })?;
+    let odd = first & 0x10 != 0;
+    Ok(odd
+        .then_some(first & 0x0f)
+        .into_iter()
+        .chain(rest.iter().flat_map(|byte| [byte >> 4, byte & 0x0f]))
+        .collect())
+}
+
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+fn from_nibbles(nibbles: &[u8]) -> Option<Vec<u8>> {
+    let (pairs, odd) = nibbles.as_chunks::<2>();
+    odd.is_empty()
+        .then(|| pairs.iter().map(|[high, low]| (high << 4) | low).collect())
+}
+
+/// Walks the part of the trie that is covered by the prefix, collecting the values.
+struct ProofWalk {
+    nodes: HashMap<CryptoHash, RawTrieNode>,
+    prefix: Vec<u8>,
+}
+
+impl ProofWalk {
+    /// Whether the subtree at the path can contain the keys with the prefix.
+    fn covers(&self, path: &[u8]) -> bool {
+        path.starts_with(&self.prefix) || self.prefix.starts_with(path)
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn add_value(
+        &self,
+        path: &[u8],
+        value: &ValueRef,
+        values: &mut BTreeMap<Vec<u8>, ValueRef>,
+    ) -> Result<(), StateProofError> {
+        if !path.starts_with(&self.prefix) {
+            return Ok(());
+        }
+        let key = from_nibbles(path).ok_or_else(|| {
+            StateProofError::InvalidProofNode(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "value at the odd number of nibbles",
+            ))
+        })?;
+        values.insert(key, value.clone());
+        Ok(())
+    }
+
+    /// Visits the node, which can be reached by the different paths, as the equal subtrees share the nodes.
+    #[allow(clippy::result_large_err)]
+    fn visit(
+        &self,
+        hash: &CryptoHash,
+        path: &mut Vec<u8>,
+        values: &mut BTreeMap<Vec<u8>, ValueRef>,
+    ) -> Result<(), StateProofError> {
+        let node = self
+            .nodes
+            .get(hash)
+            .ok_or(StateProofError::MissingProofNode(*hash))?;
+        let path_len = path.len();
+        match node {
+            RawTrieNode::Leaf(key, value) => {
+                path.extend(decode_nibbles(key)?);
+                self.add_value(path, value, values)?;
+            }
+            RawTrieNode::Extension(key, child) => {
+                path.extend(decode_nibbles(key)?);
+                if self.covers(path) {
+                    self.visit(child, path, values)?;
+                }
+            }
+            RawTrieNode::BranchNoValue(children) | RawTrieNode::BranchWithValue(_, children) => {
+                if let RawTrieNode::BranchWithValue(value, _) = node {
+                    self.add_value(path, value, values)?;
+                }
+                for (nibble, child) in (0..).zip(&children.0) {
+                    let Some(child) = child else {
+                        continue;
+                    };
+                    path.push(nibble);
+                    if self.covers(path) {
+                        self.visit(child, path, values)?;
+                    }
+                    path.pop();
+                }
+            }
+        }
+        path.truncate(path_len);
+        Ok(())
+    }
+}
+
+/// Verifies that the `view_state` result holds exactly the contract state under the prefix
+/// that is committed to by the state root, using the trie nodes from the proof.
+#[allow(clippy::result_large_err)]
+pub fn verify_state_proof(
+    state_root: &CryptoHash,
+    account_id: &AccountId,
+    prefix: &[u8],
+    result: &ViewStateResult,
+) -> Result<(), StateProofError> {
+    let nodes = result
+        .proof
+        .iter()
+        .map(|node| {
+            let hash = CryptoHash::hash_bytes(node);
+            RawTrieNodeWithSize::try_from_slice(node)
+                .map(|node| (hash, node.node))
+                .map_err(StateProofError::InvalidProofNode)
+        })
+        .collect::<Result<_, _>>()?;
+
+    let account_prefix = TrieKey::ContractData {
+        account_id: account_id.clone(),
+        key: vec![],
+    }
+    .to_vec();
+    let walk = ProofWalk {
+        nodes,
+        prefix: to_nibbles(&[&account_prefix, prefix].concat()),
+    };
+    let mut values = BTreeMap::new();
+    // The default hash is the root of the empty trie
+    if *state_root != CryptoHash::default() {
+        walk.visit(state_root, &mut vec![], &mut values)?;
+    }
+
+    for item in &result.values {
+        let key = [&account_prefix, item.key.as_slice()].concat();
+        match values.remove(&key) {
+            Some(value) if value == ValueRef::new(&item.value) => {}
+            _ => return Err(StateProofError::UnprovenValue(item.key.to_vec())),
+        }
+    }
+    if let Some(key) = values.into_keys().next() {
+        return Err(StateProofError::MissingValue(
+            key[account_prefix.len()..].to_vec(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use near_primitives::views::StateItem;
+
+    use super::*;
+
+    fn encode_nibbles(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+        let (first, rest) = if !nibbles.len().is_multiple_of(2) {
+            (0x10 | nibbles[0], &nibbles[1..])
+        } else {
+            (0, nibbles)
+        };
+        let first = if is_leaf { first | 0x20 } else { first };
+        std::iter::once(first)
+            .chain(
+                rest.as_chunks::<2>()
+                    .0
+                    .iter()
+                    .map(|[high, low]| (high << 4) | low),
+            )
+            .collect()
+    }
+
+    fn node(node: RawTrieNode, proof: &mut Vec<Arc<[u8]>>) -> CryptoHash {
+        let bytes = borsh::to_vec(&RawTrieNodeWithSize {
+            node,
+            memory_usage: 0,
+        })
+        .unwrap();
+        let hash = CryptoHash::hash_bytes(&bytes);
+        proof.push(bytes.into());
+        hash
+    }
+
+    fn item(key: &[u8], value: &[u8]) -> StateItem {
+        StateItem {
+            key: key.to_vec().into(),
+            value: value.to_vec().into(),
+        }
+    }
+
+    /// Builds the trie with the `a` and `b` keys of the contract, and the other account under the unknown node.
+    fn state(account_id: &AccountId) -> (CryptoHash, Vec<Arc<[u8]>>) {
+        let mut proof = vec![];
+        let leaf = |value: &[u8], proof: &mut Vec<_>| {
+            node(
+                RawTrieNode::Leaf(encode_nibbles(&[], true), ValueRef::new(value)),
+                proof,
+            )
+        };
+        let mut children = [None; 16];
+        children[1] = Some(leaf(b"A", &mut proof));
+        children[2] = Some(leaf(b"BB", &mut proof));
+        let branch = node(RawTrieNode::BranchNoValue(Children(children)), &mut proof);
+
+        // Both keys start with the `6` nibble, and the branch takes the second one
+        let path = to_nibbles(
+            &TrieKey::ContractData {
+                account_id: account_id.clone(),
+                key: vec![0x60],
+            }
+            .to_vec(),
+        );
+        let extension = node(
+            RawTrieNode::Extension(encode_nibbles(&path[1..path.len() - 1], false), branch),
+            &mut proof,
+        );
+        let mut children = [None; 16];
+        children[path[0] as usize] = Some(extension);
+        children[0xf] = Some(CryptoHash::hash_bytes(b"not in the proof"));
+        let root = node(RawTrieNode::BranchNoValue(Children(children)), &mut proof);
+        (root, proof)
+    }
+
+    #[test]
+    #[allow(clippy::result_large_err)]
+    fn state_proof_is_verified() {
+        let account_id: AccountId = "contract.testnet".parse().unwrap();
+        let (root, proof) = state(&account_id);
+        let result = |values: Vec<StateItem>| ViewStateResult {
+            values,
+            proof: proof.clone(),
+        };
+        let verify =
+            |prefix: &[u8], values| verify_state_proof(&root, &account_id, prefix, &result(values));
+
+        verify(b"", vec![item(b"a", b"A"), item(b"b", b"BB")]).unwrap();
+        verify(b"b", vec![item(b"b", b"BB")]).unwrap();
+        verify(b"c", vec![]).unwrap();
+
+        assert!(matches!(
+            verify(b"", vec![item(b"a", b"A"), item(b"b", b"B")]),
+            Err(StateProofError::UnprovenValue(key)) if key == b"b"
+        ));
+        assert!(matches!(
+            verify(b"", vec![item(b"a", b"A"), item(b"b", b"BB"), item(b"c", b"C")]),
+            Err(StateProofError::UnprovenValue(key)) if key == b"c"
+        ));
+        assert!(matches!(
+            verify(b"", vec![item(b"b", b"BB")]),
+            Err(StateProofError::MissingValue(key)) if key == b"a"
+        ));
+
+        let incomplete = ViewStateResult {
+            values: vec![item(b"b", b"BB")],
+            proof: proof[1..].to_vec(),
+        };
+        assert!(matches!(
+            verify_state_proof(&root, &account_id, b"", &incomplete),
+            Err(StateProofError::MissingProofNode(_))
+        ));
+    }
+}
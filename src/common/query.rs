@@ -1,30 +1,59 @@
-use std::{marker::PhantomData, sync::Arc};
-
-use futures::future::join_all;
-use near_jsonrpc_client::methods::{
-    block::{RpcBlockError, RpcBlockRequest},
-    query::{RpcQueryError, RpcQueryRequest, RpcQueryResponse},
-    validators::{RpcValidatorError, RpcValidatorRequest},
-    RpcMethod,
+use std::{marker::PhantomData, ops::RangeInclusive, sync::Arc};
+
+use futures::{future::join_all, Stream, StreamExt, TryStreamExt};
+use near_jsonrpc_client::{
+    errors::{JsonRpcError, JsonRpcServerError},
+    methods::{
+        block::{RpcBlockError, RpcBlockRequest},
+        chunk::{RpcChunkError, RpcChunkRequest},
+        gas_price::{RpcGasPriceError, RpcGasPriceRequest},
+        network_info::{RpcNetworkInfoError, RpcNetworkInfoRequest, RpcNetworkInfoResponse},
+        query::{RpcQueryError, RpcQueryRequest, RpcQueryResponse},
+        status::{RpcStatusError, RpcStatusRequest},
+        tx::{
+            RpcTransactionError, RpcTransactionResponse, RpcTransactionStatusRequest,
+            TransactionInfo,
+        },
+        validators::{RpcValidatorError, RpcValidatorRequest},
+        EXPERIMENTAL_genesis_config::{
+            RpcGenesisConfigError, RpcGenesisConfigRequest, RpcGenesisConfigResponse,
+        },
+        EXPERIMENTAL_protocol_config::{
+            RpcProtocolConfigError, RpcProtocolConfigRequest, RpcProtocolConfigResponse,
+        },
+        EXPERIMENTAL_tx_status::RpcTransactionStatusRequest as RpcTransactionStatusWithReceiptsRequest,
+        RpcMethod,
+    },
+    MethodCallResult,
 };
 use near_primitives::{
-    types::{BlockReference, EpochReference},
+    hash::CryptoHash,
+    types::{AccountId, BlockHeight, BlockReference, EpochReference},
     views::{
-        AccessKeyList, AccessKeyView, AccountView, BlockView, ContractCodeView, EpochValidatorInfo,
-        QueryRequest, ViewStateResult,
+        AccessKeyList, AccessKeyView, AccountView, BlockView, ChunkView, ContractCodeView,
+        EpochValidatorInfo, FinalExecutionOutcomeViewEnum, FinalExecutionOutcomeWithReceiptView,
+        GasPriceView, QueryRequest, StatusResponse, TxExecutionStatus, ViewStateResult,
     },
 };
 use serde::de::DeserializeOwned;
 use tracing::{debug, error, info, instrument, trace, warn};
 
 use crate::{
-    config::{retry, NetworkConfig, RetryResponse},
-    errors::QueryError,
-    types::Data,
+    config::{hedged_retry, retry, NetworkConfig, OperationKind, RetryResponse},
+    errors::{BatchError, QueryError, RetryError, RpcCallError},
+    types::{
+        collections::StorageLayout,
+        reference::{ChunkReference, Reference},
+        transactions::TransactionStatus,
+        Data,
+    },
 };
 
 use super::utils::{
-    is_critical_blocks_error, is_critical_query_error, is_critical_validator_error,
+    is_critical_blocks_error, is_critical_chunk_error, is_critical_gas_price_error,
+    is_critical_genesis_config_error, is_critical_network_info_error,
+    is_critical_protocol_config_error, is_critical_query_error, is_critical_status_error,
+    is_critical_transaction_status_error, is_critical_validator_error, is_endpoint_failure,
 };
 
 const QUERY_EXECUTOR_TARGET: &str = "near_api::query::executor";
@@ -42,6 +71,7 @@ where
     // TODO: Add error type
 
     /// NOTE: responses should always >= 1
+    #[allow(clippy::result_large_err)]
     fn process_response(
         &self,
         responses: Vec<Self::QueryResponse>,
@@ -56,6 +86,7 @@ where
     Method::Error: std::fmt::Display + std::fmt::Debug + Sync + Send,
 {
     type RpcReference;
+    #[allow(clippy::result_large_err)]
     fn create_query(
         &self,
         network: &NetworkConfig,
@@ -140,11 +171,217 @@ impl QueryCreator<RpcBlockRequest> for SimpleBlockRpc {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct SimpleProtocolConfigRpc;
+
+impl QueryCreator<RpcProtocolConfigRequest> for SimpleProtocolConfigRpc {
+    type RpcReference = BlockReference;
+    fn create_query(
+        &self,
+        _network: &NetworkConfig,
+        reference: BlockReference,
+    ) -> ResultWithMethod<RpcProtocolConfigRequest, RpcProtocolConfigRequest> {
+        Ok(RpcProtocolConfigRequest {
+            block_reference: reference,
+        })
+    }
+
+    fn is_critical_error(
+        &self,
+        error: &near_jsonrpc_client::errors::JsonRpcError<RpcProtocolConfigError>,
+    ) -> bool {
+        is_critical_protocol_config_error(error)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SimpleChunkRpc;
+
+impl QueryCreator<RpcChunkRequest> for SimpleChunkRpc {
+    type RpcReference = ChunkReference;
+    fn create_query(
+        &self,
+        _network: &NetworkConfig,
+        reference: ChunkReference,
+    ) -> ResultWithMethod<RpcChunkRequest, RpcChunkRequest> {
+        Ok(RpcChunkRequest {
+            chunk_reference: reference.into(),
+        })
+    }
+
+    fn is_critical_error(
+        &self,
+        error: &near_jsonrpc_client::errors::JsonRpcError<RpcChunkError>,
+    ) -> bool {
+        is_critical_chunk_error(error)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SimpleStatusRpc;
+
+impl QueryCreator<RpcStatusRequest> for SimpleStatusRpc {
+    type RpcReference = ();
+    fn create_query(
+        &self,
+        _network: &NetworkConfig,
+        _reference: (),
+    ) -> ResultWithMethod<RpcStatusRequest, RpcStatusRequest> {
+        Ok(RpcStatusRequest)
+    }
+
+    fn is_critical_error(
+        &self,
+        error: &near_jsonrpc_client::errors::JsonRpcError<RpcStatusError>,
+    ) -> bool {
+        is_critical_status_error(error)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SimpleGenesisConfigRpc;
+
+impl QueryCreator<RpcGenesisConfigRequest> for SimpleGenesisConfigRpc {
+    type RpcReference = ();
+    fn create_query(
+        &self,
+        _network: &NetworkConfig,
+        _reference: (),
+    ) -> ResultWithMethod<RpcGenesisConfigRequest, RpcGenesisConfigRequest> {
+        Ok(RpcGenesisConfigRequest)
+    }
+
+    fn is_critical_error(
+        &self,
+        error: &near_jsonrpc_client::errors::JsonRpcError<RpcGenesisConfigError>,
+    ) -> bool {
+        is_critical_genesis_config_error(error)
+    }
+}
+
+/// The gas price is only available at the specific block, so the finality references query the latest block.
+#[derive(Clone, Debug)]
+pub struct SimpleGasPriceRpc;
+
+impl QueryCreator<RpcGasPriceRequest> for SimpleGasPriceRpc {
+    type RpcReference = BlockReference;
+    fn create_query(
+        &self,
+        _network: &NetworkConfig,
+        reference: BlockReference,
+    ) -> ResultWithMethod<RpcGasPriceRequest, RpcGasPriceRequest> {
+        let block_id = match reference {
+            BlockReference::BlockId(block_id) => Some(block_id),
+            BlockReference::Finality(_) | BlockReference::SyncCheckpoint(_) => None,
+        };
+        Ok(RpcGasPriceRequest { block_id })
+    }
+
+    fn is_critical_error(
+        &self,
+        error: &near_jsonrpc_client::errors::JsonRpcError<RpcGasPriceError>,
+    ) -> bool {
+        is_critical_gas_price_error(error)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SimpleNetworkInfoRpc;
+
+impl QueryCreator<RpcNetworkInfoRequest> for SimpleNetworkInfoRpc {
+    type RpcReference = ();
+    fn create_query(
+        &self,
+        _network: &NetworkConfig,
+        _reference: (),
+    ) -> ResultWithMethod<RpcNetworkInfoRequest, RpcNetworkInfoRequest> {
+        Ok(RpcNetworkInfoRequest)
+    }
+
+    fn is_critical_error(
+        &self,
+        error: &near_jsonrpc_client::errors::JsonRpcError<RpcNetworkInfoError>,
+    ) -> bool {
+        is_critical_network_info_error(error)
+    }
+}
+
+/// Looks up the transaction by its hash and sender. The reference is the execution status to wait for.
+#[derive(Clone, Debug)]
+pub struct SimpleTransactionStatusRpc {
+    pub tx_hash: CryptoHash,
+    pub sender_id: AccountId,
+}
+
+impl SimpleTransactionStatusRpc {
+    fn transaction_info(&self) -> TransactionInfo {
+        TransactionInfo::TransactionId {
+            tx_hash: self.tx_hash,
+            sender_account_id: self.sender_id.clone(),
+        }
+    }
+}
+
+impl QueryCreator<RpcTransactionStatusRequest> for SimpleTransactionStatusRpc {
+    type RpcReference = TxExecutionStatus;
+    fn create_query(
+        &self,
+        _network: &NetworkConfig,
+        reference: TxExecutionStatus,
+    ) -> ResultWithMethod<RpcTransactionStatusRequest, RpcTransactionStatusRequest> {
+        Ok(RpcTransactionStatusRequest {
+            transaction_info: self.transaction_info(),
+            wait_until: reference,
+        })
+    }
+
+    fn is_critical_error(
+        &self,
+        error: &near_jsonrpc_client::errors::JsonRpcError<RpcTransactionError>,
+    ) -> bool {
+        is_critical_transaction_status_error(error)
+    }
+}
+
+impl QueryCreator<RpcTransactionStatusWithReceiptsRequest> for SimpleTransactionStatusRpc {
+    type RpcReference = TxExecutionStatus;
+    fn create_query(
+        &self,
+        _network: &NetworkConfig,
+        reference: TxExecutionStatus,
+    ) -> ResultWithMethod<
+        RpcTransactionStatusWithReceiptsRequest,
+        RpcTransactionStatusWithReceiptsRequest,
+    > {
+        Ok(RpcTransactionStatusWithReceiptsRequest {
+            transaction_info: self.transaction_info(),
+            wait_until: reference,
+        })
+    }
+
+    fn is_critical_error(
+        &self,
+        error: &near_jsonrpc_client::errors::JsonRpcError<RpcTransactionError>,
+    ) -> bool {
+        is_critical_transaction_status_error(error)
+    }
+}
+
 pub type QueryBuilder<T> = RpcBuilder<T, RpcQueryRequest, BlockReference>;
 pub type MultiQueryBuilder<T> = MultiRpcBuilder<T, RpcQueryRequest, BlockReference>;
 
 pub type ValidatorQueryBuilder<T> = RpcBuilder<T, RpcValidatorRequest, EpochReference>;
 pub type BlockQueryBuilder<T> = RpcBuilder<T, RpcBlockRequest, BlockReference>;
+pub type ProtocolConfigQueryBuilder<T> = RpcBuilder<T, RpcProtocolConfigRequest, BlockReference>;
+pub type ChunkQueryBuilder<T> = RpcBuilder<T, RpcChunkRequest, ChunkReference>;
+pub type StatusQueryBuilder<T> = RpcBuilder<T, RpcStatusRequest, ()>;
+pub type GenesisConfigQueryBuilder<T> = RpcBuilder<T, RpcGenesisConfigRequest, ()>;
+pub type GasPriceQueryBuilder<T> = RpcBuilder<T, RpcGasPriceRequest, BlockReference>;
+pub type NetworkInfoQueryBuilder<T> = RpcBuilder<T, RpcNetworkInfoRequest, ()>;
+pub type TransactionStatusQueryBuilder<T> =
+    RpcBuilder<T, RpcTransactionStatusRequest, TxExecutionStatus>;
+pub type TransactionStatusWithReceiptsQueryBuilder<T> =
+    RpcBuilder<T, RpcTransactionStatusWithReceiptsRequest, TxExecutionStatus>;
 
 pub struct MultiRpcBuilder<ResponseHandler, Method, Reference>
 where
@@ -196,40 +433,102 @@ where
     pub async fn fetch_from(
         self,
         network: &NetworkConfig,
+    ) -> ResultWithMethod<Handler::Response, Method> {
+        self.observed_fetch(network, self.reference.clone()).await
+    }
+
+    #[allow(clippy::result_large_err)]
+    async fn observed_fetch(
+        &self,
+        network: &NetworkConfig,
+        reference: Reference,
+    ) -> ResultWithMethod<Handler::Response, Method> {
+        let started_at = std::time::Instant::now();
+        let result = self.fetch(network, reference).await;
+        network.observe_operation(OperationKind::MultiQuery, started_at, result.is_ok());
+        result
+    }
+
+    #[allow(clippy::result_large_err)]
+    async fn fetch(
+        &self,
+        network: &NetworkConfig,
+        reference: Reference,
     ) -> ResultWithMethod<Handler::Response, Method> {
         debug!(target: QUERY_EXECUTOR_TARGET, "Preparing queries");
         let requests: Vec<_> = self
             .requests
-            .into_iter()
+            .iter()
             .map(|request| {
                 request
-                    .create_query(network, self.reference.clone())
-                    .map(|query| (query, request))
+                    .create_query(network, reference.clone())
+                    .map(|query| {
+                        let cached = network.cached_response(&query);
+                        (query, request, cached)
+                    })
             })
             .collect::<Result<_, _>>()?;
 
-        info!(target: QUERY_EXECUTOR_TARGET, "Sending {} queries", requests.len());
-        let requests = requests.into_iter().map(|(query, request)| async move {
-            retry(network.clone(), |json_rpc_client| {
-                let query = &query;
-                let request = &request;
-
-                async move {
-                    let result = match json_rpc_client.call(&query).await {
-                        Ok(result) => RetryResponse::Ok(result),
-                        Err(err) if request.is_critical_error(&err) => RetryResponse::Critical(err),
-                        Err(err) => RetryResponse::Retry(err),
-                    };
-                    tracing::debug!(
-                        target: QUERY_EXECUTOR_TARGET,
-                        "Querying RPC with {:?} resulted in {:?}",
-                        query,
-                        result
-                    );
-                    result
+        // The cached responses are served before the retries, so only the rest is sent
+        let uncached: Vec<_> = requests
+            .iter()
+            .filter(|(_, _, cached)| cached.is_none())
+            .map(|(query, _, _)| query)
+            .collect();
+        let mut batched = if network.batch_requests && uncached.len() > 1 {
+            fetch_batch(network, &uncached).await.map(Vec::into_iter)
+        } else {
+            None
+        };
+
+        info!(target: QUERY_EXECUTOR_TARGET, "Sending {} queries", uncached.len());
+        let requests = requests.into_iter().map(|(query, request, cached)| {
+            let batched = match cached {
+                Some(_) => None,
+                None => batched.as_mut().and_then(Iterator::next),
+            };
+            async move {
+                if let Some(response) = cached {
+                    return Ok(response);
                 }
-            })
-            .await
+                match batched {
+                    Some(Ok(response)) => return Ok(response),
+                    Some(Err(err)) if request.is_critical_error(&err) => {
+                        return Err(RetryError::Critical(err))
+                    }
+                    // Retryable errors are retried one by one
+                    _ => {}
+                }
+
+                hedged_retry(network.clone(), |rpc_client| {
+                    let query = &query;
+                    let request = &request;
+
+                    async move {
+                        let result = match rpc_client.call_coalesced(&query).await {
+                            Ok(result) => RetryResponse::Ok(result),
+                            Err(RpcCallError::TransportError(err)) => RetryResponse::Transport(err),
+                            Err(RpcCallError::JsonRpcError(err))
+                                if request.is_critical_error(&err) =>
+                            {
+                                RetryResponse::Critical(err)
+                            }
+                            Err(RpcCallError::JsonRpcError(err)) if !is_endpoint_failure(&err) => {
+                                RetryResponse::Pending(err)
+                            }
+                            Err(RpcCallError::JsonRpcError(err)) => RetryResponse::Retry(err),
+                        };
+                        tracing::debug!(
+                            target: QUERY_EXECUTOR_TARGET,
+                            "Querying RPC with {:?} resulted in {:?}",
+                            query,
+                            result
+                        );
+                        result
+                    }
+                })
+                .await
+            }
         });
 
         let requests: Vec<_> = join_all(requests)
@@ -245,17 +544,93 @@ where
         self.handler.process_response(requests)
     }
 
+    #[cfg(feature = "blocking")]
+    #[allow(clippy::result_large_err)]
+    pub fn fetch_from_blocking(
+        self,
+        network: &NetworkConfig,
+    ) -> Result<Handler::Response, crate::errors::BlockingError<QueryError<Method>>> {
+        crate::blocking::block_on(self.fetch_from(network))
+    }
+
+    #[allow(clippy::result_large_err)]
     pub async fn fetch_from_mainnet(self) -> ResultWithMethod<Handler::Response, Method> {
         let network = NetworkConfig::mainnet();
         self.fetch_from(&network).await
     }
 
+    #[allow(clippy::result_large_err)]
     pub async fn fetch_from_testnet(self) -> ResultWithMethod<Handler::Response, Method> {
         let network = NetworkConfig::testnet();
         self.fetch_from(&network).await
     }
 }
 
+/// Sends the queries as one JSON-RPC batch, or returns `None` if they should be sent one by one.
+async fn fetch_batch<Method>(
+    network: &NetworkConfig,
+    queries: &[&Method],
+) -> Option<Vec<MethodCallResult<Method::Response, Method::Error>>>
+where
+    Method: RpcMethod + std::fmt::Debug + Send + Sync,
+    Method::Response: Send,
+    Method::Error: std::fmt::Display + std::fmt::Debug + Sync + Send,
+{
+    info!(target: QUERY_EXECUTOR_TARGET, "Sending {} queries as a batch", queries.len());
+    let result = retry(network.clone(), |rpc_client| async move {
+        let url = &rpc_client.endpoint().url;
+        if network.health.batch_rejected(url) {
+            return RetryResponse::Critical(BatchError::Rejected(format!(
+                "{url} rejected the batch before"
+            )));
+        }
+        match rpc_client.call_batch(queries).await {
+            Ok(responses) => RetryResponse::Ok(responses),
+            Err(err @ BatchError::Rejected(_)) => {
+                network.health.record_batch_rejected(url);
+                RetryResponse::Critical(err)
+            }
+            // The queries are retried one by one anyway, so the batch isn't retried
+            // not to spend the retry budget twice
+            Err(err) => RetryResponse::Critical(err),
+        }
+    })
+    .await;
+
+    match result {
+        Ok(responses) => Some(responses),
+        Err(err) => {
+            warn!(target: QUERY_EXECUTOR_TARGET, "Batch request failed, sending the queries one by one: {err}");
+            None
+        }
+    }
+}
+
+impl<Handler, Method, Reference> MultiRpcBuilder<MultiQueryHandler<Vec<Handler>>, Method, Reference>
+where
+    Handler: Send + Sync,
+    Reference: Send + Sync,
+{
+    /// Combines the queries of the same type into one multi query,
+    /// e.g. to fetch the balances of many accounts at the same block.
+    ///
+    /// The references of the builders are ignored in favor of the given one.
+    pub fn from_builders(
+        builders: impl IntoIterator<Item = RpcBuilder<Handler, Method, Reference>>,
+        reference: Reference,
+    ) -> Self {
+        let (requests, handlers) = builders
+            .into_iter()
+            .map(|builder| (builder.request, builder.handler))
+            .unzip();
+        Self {
+            reference,
+            requests,
+            handler: MultiQueryHandler::new(handlers),
+        }
+    }
+}
+
 pub struct RpcBuilder<Handler, Method, Reference> {
     reference: Reference,
     request: Arc<dyn QueryCreator<Method, RpcReference = Reference> + Send + Sync>,
@@ -293,18 +668,55 @@ where
     pub async fn fetch_from(
         self,
         network: &NetworkConfig,
+    ) -> ResultWithMethod<Handler::Response, Method> {
+        let Self {
+            reference,
+            request,
+            handler,
+        } = self;
+        Self::observed_fetch(&request, &handler, network, reference).await
+    }
+
+    #[allow(clippy::result_large_err)]
+    async fn observed_fetch(
+        request: &Arc<dyn QueryCreator<Method, RpcReference = Reference> + Send + Sync>,
+        handler: &Handler,
+        network: &NetworkConfig,
+        reference: Reference,
+    ) -> ResultWithMethod<Handler::Response, Method> {
+        let started_at = std::time::Instant::now();
+        let result = Self::fetch(request, handler, network, reference).await;
+        network.observe_operation(OperationKind::Query, started_at, result.is_ok());
+        result
+    }
+
+    #[allow(clippy::result_large_err)]
+    async fn fetch(
+        request: &Arc<dyn QueryCreator<Method, RpcReference = Reference> + Send + Sync>,
+        handler: &Handler,
+        network: &NetworkConfig,
+        reference: Reference,
     ) -> ResultWithMethod<Handler::Response, Method> {
         debug!(target: QUERY_EXECUTOR_TARGET, "Preparing query");
-        let query = self.request.create_query(network, self.reference)?;
+        let query = request.create_query(network, reference)?;
 
-        let query_response = retry(network.clone(), |json_rpc_client| {
+        if let Some(response) = network.cached_response(&query) {
+            debug!(target: QUERY_EXECUTOR_TARGET, "Processing cached query response");
+            return handler.process_response(vec![response]);
+        }
+        let query_response = hedged_retry(network.clone(), |rpc_client| {
             let query = &query;
-            let request = &self.request;
             async move {
-                let result = match json_rpc_client.call(&query).await {
+                let result = match rpc_client.call_coalesced(&query).await {
                     Ok(result) => RetryResponse::Ok(result),
-                    Err(err) if request.is_critical_error(&err) => RetryResponse::Critical(err),
-                    Err(err) => RetryResponse::Retry(err),
+                    Err(RpcCallError::TransportError(err)) => RetryResponse::Transport(err),
+                    Err(RpcCallError::JsonRpcError(err)) if request.is_critical_error(&err) => {
+                        RetryResponse::Critical(err)
+                    }
+                    Err(RpcCallError::JsonRpcError(err)) if !is_endpoint_failure(&err) => {
+                        RetryResponse::Pending(err)
+                    }
+                    Err(RpcCallError::JsonRpcError(err)) => RetryResponse::Retry(err),
                 };
                 tracing::debug!(
                     target: QUERY_EXECUTOR_TARGET,
@@ -318,17 +730,474 @@ where
         .await?;
 
         debug!(target: QUERY_EXECUTOR_TARGET, "Processing query response");
-        self.handler.process_response(vec![query_response])
+        handler.process_response(vec![query_response])
+    }
+
+    #[cfg(feature = "blocking")]
+    #[allow(clippy::result_large_err)]
+    pub fn fetch_from_blocking(
+        self,
+        network: &NetworkConfig,
+    ) -> Result<Handler::Response, crate::errors::BlockingError<QueryError<Method>>> {
+        crate::blocking::block_on(self.fetch_from(network))
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub async fn fetch_from_mainnet(self) -> ResultWithMethod<Handler::Response, Method> {
+        let network = NetworkConfig::mainnet();
+        self.fetch_from(&network).await
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub async fn fetch_from_testnet(self) -> ResultWithMethod<Handler::Response, Method> {
+        let network = NetworkConfig::testnet();
+        self.fetch_from(&network).await
+    }
+}
+
+/// The responses of the historical series with the heights they are read at.
+type Series<Response> = Vec<(BlockHeight, Response)>;
+
+/// How many queries of the historical series are sent concurrently by default.
+const DEFAULT_SERIES_CONCURRENCY: usize = 8;
+
+/// Runs the query at each of the block heights, see [RpcBuilder::series] and [MultiRpcBuilder::series].
+///
+/// The old blocks are available only on the archival nodes, so the network should use the archival endpoints,
+/// as the default [NetworkConfig::mainnet] and [NetworkConfig::testnet] do.
+pub struct SeriesBuilder<Builder> {
+    builder: Builder,
+    heights: Vec<BlockHeight>,
+    concurrency: usize,
+}
+
+impl<Builder> SeriesBuilder<Builder> {
+    fn new(builder: Builder, heights: impl IntoIterator<Item = BlockHeight>) -> Self {
+        Self {
+            builder,
+            heights: heights.into_iter().collect(),
+            concurrency: DEFAULT_SERIES_CONCURRENCY,
+        }
+    }
+
+    fn in_range(builder: Builder, range: RangeInclusive<BlockHeight>, step: BlockHeight) -> Self {
+        let step = usize::try_from(step).unwrap_or(usize::MAX).max(1);
+        Self::new(builder, range.step_by(step))
+    }
+
+    /// Sets how many queries are sent concurrently.
+    pub fn with_concurrency(self, concurrency: usize) -> Self {
+        Self {
+            concurrency: concurrency.max(1),
+            ..self
+        }
+    }
+
+    /// Runs the query at each height, in order of the heights. The heights without a block are skipped.
+    ///
+    /// It stops at the first error, e.g. if the block is garbage collected on the non-archival node.
+    #[allow(clippy::result_large_err)]
+    async fn fetch<Response, Fetch, Fut>(
+        &self,
+        fetch: Fetch,
+    ) -> ResultWithMethod<Series<Response>, RpcQueryRequest>
+    where
+        Builder: Sync,
+        Response: Send,
+        Fetch: Fn(BlockReference) -> Fut + Sync,
+        Fut: std::future::Future<Output = ResultWithMethod<Response, RpcQueryRequest>> + Send,
+    {
+        info!(target: QUERY_EXECUTOR_TARGET, "Querying {} heights", self.heights.len());
+        futures::stream::iter(self.heights.iter().map(|&height| {
+            let response = fetch(Reference::AtBlock(height).into());
+            async move { (height, response.await) }
+        }))
+        .buffered(self.concurrency)
+        .filter_map(|(height, result)| async move {
+            match result {
+                Ok(response) => Some(Ok((height, response))),
+                Err(err) if is_unknown_block_error(&err) => {
+                    debug!(target: QUERY_EXECUTOR_TARGET, "Skipping height {height} without a block");
+                    None
+                }
+                Err(err) => Some(Err(err)),
+            }
+        })
+        .try_collect()
+        .await
+    }
+}
+
+const fn is_unknown_block_error(err: &QueryError<RpcQueryRequest>) -> bool {
+    matches!(
+        err,
+        QueryError::JsonRpcError(RetryError::Critical(JsonRpcError::ServerError(
+            JsonRpcServerError::HandlerError(RpcQueryError::UnknownBlock { .. })
+        )))
+    )
+}
+
+impl<Handler> SeriesBuilder<QueryBuilder<Handler>>
+where
+    Handler:
+        ResponseHandler<QueryResponse = RpcQueryResponse, Method = RpcQueryRequest> + Send + Sync,
+    Handler::Response: Send,
+{
+    #[allow(clippy::result_large_err)]
+    pub async fn fetch_from(
+        self,
+        network: &NetworkConfig,
+    ) -> ResultWithMethod<Series<Handler::Response>, RpcQueryRequest> {
+        let builder = &self.builder;
+        self.fetch(|reference| {
+            QueryBuilder::observed_fetch(&builder.request, &builder.handler, network, reference)
+        })
+        .await
+    }
+
+    #[cfg(feature = "blocking")]
+    #[allow(clippy::result_large_err)]
+    pub fn fetch_from_blocking(
+        self,
+        network: &NetworkConfig,
+    ) -> Result<Series<Handler::Response>, crate::errors::BlockingError<QueryError<RpcQueryRequest>>>
+    {
+        crate::blocking::block_on(self.fetch_from(network))
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub async fn fetch_from_mainnet(
+        self,
+    ) -> ResultWithMethod<Series<Handler::Response>, RpcQueryRequest> {
+        let network = NetworkConfig::mainnet();
+        self.fetch_from(&network).await
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub async fn fetch_from_testnet(
+        self,
+    ) -> ResultWithMethod<Series<Handler::Response>, RpcQueryRequest> {
+        let network = NetworkConfig::testnet();
+        self.fetch_from(&network).await
+    }
+}
+
+impl<Handler> SeriesBuilder<MultiQueryBuilder<Handler>>
+where
+    Handler:
+        ResponseHandler<QueryResponse = RpcQueryResponse, Method = RpcQueryRequest> + Send + Sync,
+    Handler::Response: Send,
+{
+    #[allow(clippy::result_large_err)]
+    pub async fn fetch_from(
+        self,
+        network: &NetworkConfig,
+    ) -> ResultWithMethod<Series<Handler::Response>, RpcQueryRequest> {
+        let builder = &self.builder;
+        self.fetch(|reference| builder.observed_fetch(network, reference))
+            .await
+    }
+
+    #[cfg(feature = "blocking")]
+    #[allow(clippy::result_large_err)]
+    pub fn fetch_from_blocking(
+        self,
+        network: &NetworkConfig,
+    ) -> Result<Series<Handler::Response>, crate::errors::BlockingError<QueryError<RpcQueryRequest>>>
+    {
+        crate::blocking::block_on(self.fetch_from(network))
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub async fn fetch_from_mainnet(
+        self,
+    ) -> ResultWithMethod<Series<Handler::Response>, RpcQueryRequest> {
+        let network = NetworkConfig::mainnet();
+        self.fetch_from(&network).await
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub async fn fetch_from_testnet(
+        self,
+    ) -> ResultWithMethod<Series<Handler::Response>, RpcQueryRequest> {
+        let network = NetworkConfig::testnet();
+        self.fetch_from(&network).await
+    }
+}
+
+impl<Handler, Method> RpcBuilder<Handler, Method, TxExecutionStatus> {
+    /// Sets the execution status the node waits for before returning the transaction, the same as [RpcBuilder::at].
+    pub fn wait_until(self, status: TxExecutionStatus) -> Self {
+        Self {
+            reference: status,
+            ..self
+        }
+    }
+}
+
+impl<Handler> QueryBuilder<Handler> {
+    /// Prepares the query to run at each of the block heights, e.g. to get the balance history.
+    pub fn series(self, heights: impl IntoIterator<Item = BlockHeight>) -> SeriesBuilder<Self> {
+        SeriesBuilder::new(self, heights)
+    }
+
+    /// Prepares the query to run at every `step`-th block height of the range.
+    pub fn series_in_range(
+        self,
+        range: RangeInclusive<BlockHeight>,
+        step: BlockHeight,
+    ) -> SeriesBuilder<Self> {
+        SeriesBuilder::in_range(self, range, step)
+    }
+}
+
+impl<Handler: Send + Sync> MultiQueryBuilder<Handler> {
+    /// Prepares the queries to run at each of the block heights, e.g. to get the balance history.
+    pub fn series(self, heights: impl IntoIterator<Item = BlockHeight>) -> SeriesBuilder<Self> {
+        SeriesBuilder::new(self, heights)
+    }
+
+    /// Prepares the queries to run at every `step`-th block height of the range.
+    pub fn series_in_range(
+        self,
+        range: RangeInclusive<BlockHeight>,
+        step: BlockHeight,
+    ) -> SeriesBuilder<Self> {
+        SeriesBuilder::in_range(self, range, step)
+    }
+}
+
+/// How often the latest block is polled to detect the new blocks for [RpcBuilder::watch].
+const WATCH_BLOCK_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// The part of the response that is compared by [RpcBuilder::watch] to detect the changes.
+///
+/// For [Data] it's the data itself, as the block it's read at changes with every block.
+/// Use [RpcBuilder::watch_by] for the responses that don't implement it.
+pub trait WatchValue {
+    type Value: PartialEq + Send;
+
+    fn watch_value(&self) -> Self::Value;
+}
+
+impl<T: PartialEq + Clone + Send> WatchValue for Data<T> {
+    type Value = T;
+
+    fn watch_value(&self) -> T {
+        self.data.clone()
+    }
+}
+
+impl<T: WatchValue> WatchValue for Vec<T> {
+    type Value = Vec<T::Value>;
+
+    fn watch_value(&self) -> Self::Value {
+        self.iter().map(WatchValue::watch_value).collect()
+    }
+}
+
+macro_rules! impl_watch_value_for_self {
+    ($($ty:ty),*) => {$(
+        impl WatchValue for $ty {
+            type Value = Self;
+
+            fn watch_value(&self) -> Self {
+                self.clone()
+            }
+        }
+    )*};
+}
+
+impl_watch_value_for_self!(
+    u64,
+    u128,
+    bool,
+    String,
+    crate::types::CryptoHash,
+    near_token::NearToken
+);
+
+macro_rules! impl_watch_value_for_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: WatchValue),+> WatchValue for ($($name,)+) {
+            type Value = ($($name::Value,)+);
+
+            #[allow(non_snake_case)]
+            fn watch_value(&self) -> Self::Value {
+                let ($($name,)+) = self;
+                ($($name.watch_value(),)+)
+            }
+        }
+    };
+}
+
+impl_watch_value_for_tuple!(A, B);
+impl_watch_value_for_tuple!(A, B, C);
+impl_watch_value_for_tuple!(A, B, C, D);
+
+#[derive(Clone, Copy, Debug)]
+enum WatchTrigger {
+    NewBlock,
+    Interval(std::time::Duration),
+}
+
+struct Watch<'a, Handler, Method, Reference, Value, Key> {
+    builder: RpcBuilder<Handler, Method, Reference>,
+    network: &'a NetworkConfig,
+    trigger: WatchTrigger,
+    key: Key,
+    last_block: Option<near_primitives::hash::CryptoHash>,
+    last_value: Option<Value>,
+    started: bool,
+}
+
+impl<Handler, Method, Reference> RpcBuilder<Handler, Method, Reference>
+where
+    Handler: ResponseHandler<QueryResponse = Method::Response, Method = Method> + Send + Sync,
+    Handler::Response: Send,
+    Method: RpcMethod + std::fmt::Debug + Send + Sync + 'static,
+    Method::Response: std::fmt::Debug + Send + Sync,
+    Method::Error: std::fmt::Display + std::fmt::Debug + Sync + Send,
+    Reference: Clone + Send + Sync,
+{
+    /// Re-executes the query on every new block, and yields the response whenever its [WatchValue] changes.
+    /// The first response is always yielded.
+    ///
+    /// The query is retried as usual, and the error is yielded once the retries are exhausted,
+    /// but the watch goes on. Drop the stream to stop watching.
+    pub fn watch<'a>(
+        self,
+        network: &'a NetworkConfig,
+    ) -> impl Stream<Item = ResultWithMethod<Handler::Response, Method>> + Send + 'a
+    where
+        Self: 'a,
+        Handler::Response: WatchValue,
+    {
+        self.watch_with(network, WatchTrigger::NewBlock, WatchValue::watch_value)
+    }
+
+    /// Same as [RpcBuilder::watch], but re-executes the query on the interval instead of every new block.
+    pub fn watch_every<'a>(
+        self,
+        network: &'a NetworkConfig,
+        interval: std::time::Duration,
+    ) -> impl Stream<Item = ResultWithMethod<Handler::Response, Method>> + Send + 'a
+    where
+        Self: 'a,
+        Handler::Response: WatchValue,
+    {
+        self.watch_with(
+            network,
+            WatchTrigger::Interval(interval),
+            WatchValue::watch_value,
+        )
+    }
+
+    /// Same as [RpcBuilder::watch], but the changes are detected by the value the `key` extracts from the response.
+    pub fn watch_by<'a, Value: PartialEq + Send + 'a>(
+        self,
+        network: &'a NetworkConfig,
+        key: impl Fn(&Handler::Response) -> Value + Send + 'a,
+    ) -> impl Stream<Item = ResultWithMethod<Handler::Response, Method>> + Send + 'a
+    where
+        Self: 'a,
+    {
+        self.watch_with(network, WatchTrigger::NewBlock, key)
+    }
+
+    /// Same as [RpcBuilder::watch_by], but re-executes the query on the interval instead of every new block.
+    pub fn watch_every_by<'a, Value: PartialEq + Send + 'a>(
+        self,
+        network: &'a NetworkConfig,
+        interval: std::time::Duration,
+        key: impl Fn(&Handler::Response) -> Value + Send + 'a,
+    ) -> impl Stream<Item = ResultWithMethod<Handler::Response, Method>> + Send + 'a
+    where
+        Self: 'a,
+    {
+        self.watch_with(network, WatchTrigger::Interval(interval), key)
     }
 
-    pub async fn fetch_from_mainnet(self) -> ResultWithMethod<Handler::Response, Method> {
-        let network = NetworkConfig::mainnet();
-        self.fetch_from(&network).await
+    fn watch_with<'a, Value: PartialEq + Send + 'a>(
+        self,
+        network: &'a NetworkConfig,
+        trigger: WatchTrigger,
+        key: impl Fn(&Handler::Response) -> Value + Send + 'a,
+    ) -> impl Stream<Item = ResultWithMethod<Handler::Response, Method>> + Send + 'a
+    where
+        Self: 'a,
+    {
+        let watch = Watch {
+            builder: self,
+            network,
+            trigger,
+            key,
+            last_block: None,
+            last_value: None,
+            started: false,
+        };
+        futures::stream::unfold(watch, |mut watch| async move {
+            loop {
+                watch.wait_for_trigger().await;
+                let builder = &watch.builder;
+                let response = Self::observed_fetch(
+                    &builder.request,
+                    &builder.handler,
+                    watch.network,
+                    builder.reference.clone(),
+                )
+                .await;
+                match response {
+                    Ok(response) => {
+                        let value = (watch.key)(&response);
+                        if watch.last_value.as_ref() != Some(&value) {
+                            watch.last_value = Some(value);
+                            return Some((Ok(response), watch));
+                        }
+                        trace!(target: QUERY_EXECUTOR_TARGET, "Watched value is unchanged");
+                    }
+                    Err(err) => return Some((Err(err), watch)),
+                }
+            }
+        })
     }
+}
 
-    pub async fn fetch_from_testnet(self) -> ResultWithMethod<Handler::Response, Method> {
-        let network = NetworkConfig::testnet();
-        self.fetch_from(&network).await
+impl<Handler, Method, Reference, Value, Key> Watch<'_, Handler, Method, Reference, Value, Key> {
+    /// Waits until the query should be re-executed. The first query is executed right away.
+    async fn wait_for_trigger(&mut self) {
+        let mut started = std::mem::replace(&mut self.started, true);
+        match self.trigger {
+            WatchTrigger::Interval(interval) => {
+                if started {
+                    tokio::time::sleep(interval).await;
+                }
+            }
+            WatchTrigger::NewBlock => loop {
+                if started {
+                    tokio::time::sleep(WATCH_BLOCK_POLL_INTERVAL).await;
+                }
+                let block = BlockQueryBuilder::new(
+                    SimpleBlockRpc,
+                    BlockReference::latest(),
+                    RpcBlockHandler,
+                )
+                .fetch_from(self.network)
+                .await;
+                match block {
+                    Ok(block) if self.last_block != Some(block.header.hash) => {
+                        self.last_block = Some(block.header.hash);
+                        return;
+                    }
+                    Ok(_) => {}
+                    // The query would fail the same way, so it's retried on the next poll
+                    Err(err) => {
+                        warn!(target: QUERY_EXECUTOR_TARGET, "Failed to poll the latest block: {err}");
+                    }
+                }
+                started = true;
+            },
+        }
     }
 }
 
@@ -337,61 +1206,161 @@ pub struct MultiQueryHandler<Handlers> {
     handlers: Handlers,
 }
 
-impl<QR, Method, H1, H2, R1, R2> ResponseHandler for MultiQueryHandler<(H1, H2)>
-where
-    Method: RpcMethod,
-    H1: ResponseHandler<QueryResponse = QR, Response = R1, Method = Method>,
-    H2: ResponseHandler<QueryResponse = QR, Response = R2, Method = Method>,
-    Method::Error: std::fmt::Display + std::fmt::Debug,
-{
-    type Response = (R1, R2);
-    type QueryResponse = QR;
-    type Method = Method;
-
-    fn process_response(&self, mut responses: Vec<QR>) -> ResultWithMethod<Self::Response, Method> {
-        let (h1, h2) = &self.handlers;
-
-        let first_response =
-            h1.process_response(responses.drain(0..h1.request_amount()).collect())?;
-        let second_response = h2.process_response(responses)?;
-
-        Ok((first_response, second_response))
-    }
+/// Implements [ResponseHandler] for the tuple of handlers, where each handler
+/// takes its [ResponseHandler::request_amount] of the responses in order.
+macro_rules! impl_multi_query_handler {
+    ($(($handler:ident, $response:ident, $index:tt)),+) => {
+        impl<QR, Method, $($handler, $response),+> ResponseHandler
+            for MultiQueryHandler<($($handler,)+)>
+        where
+            Method: RpcMethod,
+            Method::Error: std::fmt::Display + std::fmt::Debug,
+            $($handler: ResponseHandler<QueryResponse = QR, Response = $response, Method = Method>,)+
+        {
+            type Response = ($($response,)+);
+            type QueryResponse = QR;
+            type Method = Method;
+
+            fn process_response(
+                &self,
+                responses: Vec<QR>,
+            ) -> ResultWithMethod<Self::Response, Method> {
+                let mut responses = responses.into_iter();
+                Ok(($(
+                    self.handlers.$index.process_response(
+                        responses
+                            .by_ref()
+                            .take(self.handlers.$index.request_amount())
+                            .collect(),
+                    )?,
+                )+))
+            }
 
-    fn request_amount(&self) -> usize {
-        self.handlers.0.request_amount() + self.handlers.1.request_amount()
-    }
+            fn request_amount(&self) -> usize {
+                0 $(+ self.handlers.$index.request_amount())+
+            }
+        }
+    };
 }
 
-impl<QR, Method, H1, H2, H3, R1, R2, R3> ResponseHandler for MultiQueryHandler<(H1, H2, H3)>
+impl_multi_query_handler!((H1, R1, 0), (H2, R2, 1));
+impl_multi_query_handler!((H1, R1, 0), (H2, R2, 1), (H3, R3, 2));
+impl_multi_query_handler!((H1, R1, 0), (H2, R2, 1), (H3, R3, 2), (H4, R4, 3));
+impl_multi_query_handler!(
+    (H1, R1, 0),
+    (H2, R2, 1),
+    (H3, R3, 2),
+    (H4, R4, 3),
+    (H5, R5, 4)
+);
+impl_multi_query_handler!(
+    (H1, R1, 0),
+    (H2, R2, 1),
+    (H3, R3, 2),
+    (H4, R4, 3),
+    (H5, R5, 4),
+    (H6, R6, 5)
+);
+impl_multi_query_handler!(
+    (H1, R1, 0),
+    (H2, R2, 1),
+    (H3, R3, 2),
+    (H4, R4, 3),
+    (H5, R5, 4),
+    (H6, R6, 5),
+    (H7, R7, 6)
+);
+impl_multi_query_handler!(
+    (H1, R1, 0),
+    (H2, R2, 1),
+    (H3, R3, 2),
+    (H4, R4, 3),
+    (H5, R5, 4),
+    (H6, R6, 5),
+    (H7, R7, 6),
+    (H8, R8, 7)
+);
+impl_multi_query_handler!(
+    (H1, R1, 0),
+    (H2, R2, 1),
+    (H3, R3, 2),
+    (H4, R4, 3),
+    (H5, R5, 4),
+    (H6, R6, 5),
+    (H7, R7, 6),
+    (H8, R8, 7),
+    (H9, R9, 8)
+);
+impl_multi_query_handler!(
+    (H1, R1, 0),
+    (H2, R2, 1),
+    (H3, R3, 2),
+    (H4, R4, 3),
+    (H5, R5, 4),
+    (H6, R6, 5),
+    (H7, R7, 6),
+    (H8, R8, 7),
+    (H9, R9, 8),
+    (H10, R10, 9)
+);
+impl_multi_query_handler!(
+    (H1, R1, 0),
+    (H2, R2, 1),
+    (H3, R3, 2),
+    (H4, R4, 3),
+    (H5, R5, 4),
+    (H6, R6, 5),
+    (H7, R7, 6),
+    (H8, R8, 7),
+    (H9, R9, 8),
+    (H10, R10, 9),
+    (H11, R11, 10)
+);
+impl_multi_query_handler!(
+    (H1, R1, 0),
+    (H2, R2, 1),
+    (H3, R3, 2),
+    (H4, R4, 3),
+    (H5, R5, 4),
+    (H6, R6, 5),
+    (H7, R7, 6),
+    (H8, R8, 7),
+    (H9, R9, 8),
+    (H10, R10, 9),
+    (H11, R11, 10),
+    (H12, R12, 11)
+);
+
+/// Handler of the arbitrary number of the same queries, e.g. the balances of many accounts.
+impl<Handler> ResponseHandler for MultiQueryHandler<Vec<Handler>>
 where
-    Method: RpcMethod,
-    Method::Error: std::fmt::Display + std::fmt::Debug,
-    H1: ResponseHandler<QueryResponse = QR, Response = R1, Method = Method>,
-    H2: ResponseHandler<QueryResponse = QR, Response = R2, Method = Method>,
-    H3: ResponseHandler<QueryResponse = QR, Response = R3, Method = Method>,
+    Handler: ResponseHandler,
+    <Handler::Method as RpcMethod>::Error: std::fmt::Display + std::fmt::Debug,
 {
-    type Response = (R1, R2, R3);
-    type QueryResponse = QR;
-    type Method = Method;
-
-    fn process_response(&self, mut responses: Vec<QR>) -> ResultWithMethod<Self::Response, Method> {
-        let (h1, h2, h3) = &self.handlers;
-
-        let first_response =
-            h1.process_response(responses.drain(0..h1.request_amount()).collect())?;
-        let second_response = h2.process_response(
-            responses
-                .drain(h1.request_amount()..h2.request_amount())
-                .collect(),
-        )?;
-        let third_response = h3.process_response(responses)?;
+    type Response = Vec<Handler::Response>;
+    type QueryResponse = Handler::QueryResponse;
+    type Method = Handler::Method;
 
-        Ok((first_response, second_response, third_response))
+    #[allow(clippy::result_large_err)]
+    fn process_response(
+        &self,
+        responses: Vec<Self::QueryResponse>,
+    ) -> ResultWithMethod<Self::Response, Self::Method> {
+        let mut responses = responses.into_iter();
+        self.handlers
+            .iter()
+            .map(|handler| {
+                handler
+                    .process_response(responses.by_ref().take(handler.request_amount()).collect())
+            })
+            .collect()
     }
 
     fn request_amount(&self) -> usize {
-        self.handlers.0.request_amount() + self.handlers.1.request_amount()
+        self.handlers
+            .iter()
+            .map(ResponseHandler::request_amount)
+            .sum()
     }
 }
 
@@ -738,6 +1707,236 @@ impl ResponseHandler for RpcBlockHandler {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct RpcProtocolConfigHandler;
+
+impl ResponseHandler for RpcProtocolConfigHandler {
+    type Response = RpcProtocolConfigResponse;
+    type QueryResponse = RpcProtocolConfigResponse;
+    type Method = RpcProtocolConfigRequest;
+
+    fn process_response(
+        &self,
+        response: Vec<RpcProtocolConfigResponse>,
+    ) -> ResultWithMethod<Self::Response, Self::Method> {
+        let response = response
+            .into_iter()
+            .next()
+            .ok_or(QueryError::InternalErrorNoResponse)?;
+
+        info!(
+            target: QUERY_EXECUTOR_TARGET,
+            "Processed ProtocolConfig response, protocol version: {}",
+            response.protocol_version
+        );
+        Ok(response)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RpcChunkHandler;
+
+impl ResponseHandler for RpcChunkHandler {
+    type Response = ChunkView;
+    type QueryResponse = ChunkView;
+    type Method = RpcChunkRequest;
+
+    fn process_response(
+        &self,
+        response: Vec<ChunkView>,
+    ) -> ResultWithMethod<Self::Response, Self::Method> {
+        let response = response
+            .into_iter()
+            .next()
+            .ok_or(QueryError::InternalErrorNoResponse)?;
+
+        info!(
+            target: QUERY_EXECUTOR_TARGET,
+            "Processed Chunk response, hash: {:?}, shard: {}",
+            response.header.chunk_hash,
+            response.header.shard_id
+        );
+        Ok(response)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RpcStatusHandler;
+
+impl ResponseHandler for RpcStatusHandler {
+    type Response = StatusResponse;
+    type QueryResponse = StatusResponse;
+    type Method = RpcStatusRequest;
+
+    fn process_response(
+        &self,
+        response: Vec<StatusResponse>,
+    ) -> ResultWithMethod<Self::Response, Self::Method> {
+        let response = response
+            .into_iter()
+            .next()
+            .ok_or(QueryError::InternalErrorNoResponse)?;
+
+        info!(
+            target: QUERY_EXECUTOR_TARGET,
+            "Processed Status response, chain: {}, latest block height: {}",
+            response.chain_id,
+            response.sync_info.latest_block_height
+        );
+        Ok(response)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RpcGenesisConfigHandler;
+
+impl ResponseHandler for RpcGenesisConfigHandler {
+    type Response = RpcGenesisConfigResponse;
+    type QueryResponse = RpcGenesisConfigResponse;
+    type Method = RpcGenesisConfigRequest;
+
+    fn process_response(
+        &self,
+        response: Vec<RpcGenesisConfigResponse>,
+    ) -> ResultWithMethod<Self::Response, Self::Method> {
+        let response = response
+            .into_iter()
+            .next()
+            .ok_or(QueryError::InternalErrorNoResponse)?;
+
+        info!(
+            target: QUERY_EXECUTOR_TARGET,
+            "Processed GenesisConfig response, chain: {}, genesis height: {}",
+            response.chain_id,
+            response.genesis_height
+        );
+        Ok(response)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RpcGasPriceHandler;
+
+impl ResponseHandler for RpcGasPriceHandler {
+    type Response = GasPriceView;
+    type QueryResponse = GasPriceView;
+    type Method = RpcGasPriceRequest;
+
+    fn process_response(
+        &self,
+        response: Vec<GasPriceView>,
+    ) -> ResultWithMethod<Self::Response, Self::Method> {
+        let response = response
+            .into_iter()
+            .next()
+            .ok_or(QueryError::InternalErrorNoResponse)?;
+
+        info!(
+            target: QUERY_EXECUTOR_TARGET,
+            "Processed GasPrice response, gas price: {}",
+            response.gas_price
+        );
+        Ok(response)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RpcNetworkInfoHandler;
+
+impl ResponseHandler for RpcNetworkInfoHandler {
+    type Response = RpcNetworkInfoResponse;
+    type QueryResponse = RpcNetworkInfoResponse;
+    type Method = RpcNetworkInfoRequest;
+
+    fn process_response(
+        &self,
+        response: Vec<RpcNetworkInfoResponse>,
+    ) -> ResultWithMethod<Self::Response, Self::Method> {
+        let response = response
+            .into_iter()
+            .next()
+            .ok_or(QueryError::InternalErrorNoResponse)?;
+
+        info!(
+            target: QUERY_EXECUTOR_TARGET,
+            "Processed NetworkInfo response, active peers: {}",
+            response.num_active_peers
+        );
+        Ok(response)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RpcTransactionStatusHandler;
+
+impl ResponseHandler for RpcTransactionStatusHandler {
+    type Response = TransactionStatus;
+    type QueryResponse = RpcTransactionResponse;
+    type Method = RpcTransactionStatusRequest;
+
+    fn process_response(
+        &self,
+        response: Vec<RpcTransactionResponse>,
+    ) -> ResultWithMethod<Self::Response, Self::Method> {
+        let response = response
+            .into_iter()
+            .next()
+            .ok_or(QueryError::InternalErrorNoResponse)?;
+
+        let outcome = response
+            .final_execution_outcome
+            .map(FinalExecutionOutcomeViewEnum::into_outcome);
+        info!(
+            target: QUERY_EXECUTOR_TARGET,
+            "Processed TransactionStatus response, hash: {:?}, status: {:?}",
+            outcome.as_ref().map(|outcome| outcome.transaction_outcome.id),
+            response.final_execution_status
+        );
+        Ok(TransactionStatus {
+            status: response.final_execution_status,
+            outcome: outcome.map(Into::into),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RpcTransactionStatusWithReceiptsHandler;
+
+impl ResponseHandler for RpcTransactionStatusWithReceiptsHandler {
+    type Response = TransactionStatus<FinalExecutionOutcomeWithReceiptView>;
+    type QueryResponse = RpcTransactionResponse;
+    type Method = RpcTransactionStatusWithReceiptsRequest;
+
+    fn process_response(
+        &self,
+        response: Vec<RpcTransactionResponse>,
+    ) -> ResultWithMethod<Self::Response, Self::Method> {
+        let response = response
+            .into_iter()
+            .next()
+            .ok_or(QueryError::InternalErrorNoResponse)?;
+
+        // The node always responds to this request with the receipts, once the transaction is executed
+        let outcome = match response.final_execution_outcome {
+            Some(FinalExecutionOutcomeViewEnum::FinalExecutionOutcomeWithReceipt(outcome)) => {
+                Some(outcome)
+            }
+            _ => None,
+        };
+        info!(
+            target: QUERY_EXECUTOR_TARGET,
+            "Processed TransactionStatus response, hash: {:?}, status: {:?}, receipts: {:?}",
+            outcome.as_ref().map(|outcome| outcome.final_outcome.transaction_outcome.id),
+            response.final_execution_status,
+            outcome.as_ref().map(|outcome| outcome.receipts.len())
+        );
+        Ok(TransactionStatus {
+            status: response.final_execution_status,
+            outcome,
+        })
+    }
+}
+
 impl ResponseHandler for () {
     type Response = ();
     type QueryResponse = RpcQueryResponse;
@@ -751,3 +1950,344 @@ impl ResponseHandler for () {
         Ok(())
     }
 }
+
+/// Decodes the near-sdk collection from the `view_state` response with its [StorageLayout].
+#[derive(Clone, Debug)]
+pub struct CollectionHandler<Layout>(pub Layout);
+
+impl<Layout> ResponseHandler for CollectionHandler<Layout>
+where
+    Layout: StorageLayout,
+{
+    type Response = Data<Layout::Output>;
+    type QueryResponse = RpcQueryResponse;
+    type Method = RpcQueryRequest;
+
+    fn process_response(
+        &self,
+        response: Vec<RpcQueryResponse>,
+    ) -> ResultWithMethod<Self::Response, Self::Method> {
+        let response = ViewStateHandler.process_response(response)?;
+        trace!(target: QUERY_EXECUTOR_TARGET, "Decoding collection from {} state items", response.data.values.len());
+        Ok(Data {
+            data: self.0.decode(&response.data.values)?,
+            block_height: response.block_height,
+            block_hash: response.block_hash,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{
+        call_result, handler_error, network, rpc_error, rpc_result, ScriptedTransport,
+    };
+
+    use super::*;
+
+    /// Returns as many responses as it requested.
+    struct TakeHandler(usize);
+
+    impl ResponseHandler for TakeHandler {
+        type QueryResponse = u8;
+        type Response = Vec<u8>;
+        type Method = RpcQueryRequest;
+
+        fn process_response(
+            &self,
+            responses: Vec<u8>,
+        ) -> ResultWithMethod<Self::Response, Self::Method> {
+            Ok(responses)
+        }
+
+        fn request_amount(&self) -> usize {
+            self.0
+        }
+    }
+
+    #[test]
+    fn multi_query_handler_splits_responses() {
+        let handler = MultiQueryHandler::new((
+            TakeHandler(1),
+            TakeHandler(2),
+            MultiQueryHandler::new((TakeHandler(1), TakeHandler(1))),
+            TakeHandler(1),
+        ));
+        assert_eq!(handler.request_amount(), 6);
+        assert_eq!(
+            handler.process_response(vec![1, 2, 3, 4, 5, 6]).unwrap(),
+            (vec![1], vec![2, 3], (vec![4], vec![5]), vec![6])
+        );
+
+        let handler = MultiQueryHandler::new(vec![TakeHandler(2), TakeHandler(0), TakeHandler(1)]);
+        assert_eq!(handler.request_amount(), 3);
+        assert_eq!(
+            handler.process_response(vec![1, 2, 3]).unwrap(),
+            vec![vec![1, 2], vec![], vec![3]]
+        );
+    }
+
+    /// Returns the next value of the sequence as the function call result on every query.
+    fn sequence(values: impl IntoIterator<Item = u8>) -> ScriptedTransport {
+        ScriptedTransport::responses(values.into_iter().map(|value| call_result(value, 1)))
+    }
+
+    #[tokio::test]
+    async fn watch_yields_changed_values() {
+        use futures::StreamExt;
+
+        let (network, _) = network(sequence([1, 1, 2, 2, 2, 3]));
+        let request = QueryRequest::CallFunction {
+            account_id: "contract.testnet".parse().unwrap(),
+            method_name: "get".to_owned(),
+            args: vec![].into(),
+        };
+        let query = QueryBuilder::new(
+            SimpleQuery { request },
+            BlockReference::latest(),
+            CallResultHandler::<u8>(PhantomData),
+        );
+
+        let values: Vec<_> = query
+            .watch_every(&network, std::time::Duration::from_millis(1))
+            .map(|response| response.unwrap().data)
+            .take(3)
+            .collect()
+            .await;
+        assert_eq!(values, [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn watch_by_compares_keys() {
+        use futures::StreamExt;
+
+        let (network, _) = network(sequence([1, 3, 2, 4, 5]));
+        let request = QueryRequest::CallFunction {
+            account_id: "contract.testnet".parse().unwrap(),
+            method_name: "get".to_owned(),
+            args: vec![].into(),
+        };
+        let query = QueryBuilder::new(
+            SimpleQuery { request },
+            BlockReference::latest(),
+            CallResultHandler::<u8>(PhantomData),
+        );
+
+        let values: Vec<_> = query
+            .watch_every_by(&network, std::time::Duration::from_millis(1), |response| {
+                response.data % 2
+            })
+            .map(|response| response.unwrap().data)
+            .take(3)
+            .collect()
+            .await;
+        assert_eq!(values, [1, 2, 5]);
+    }
+
+    #[tokio::test]
+    async fn failed_batch_falls_back_without_retries() {
+        // Fails the batch requests, and returns the function call result for the single ones
+        let (mut network, transport) = network(ScriptedTransport::new(|_, request| {
+            if request.is_array() {
+                crate::config::RpcResponse::status(reqwest::StatusCode::SERVICE_UNAVAILABLE)
+            } else {
+                call_result(7u8, 1)
+            }
+        }));
+        network.batch_requests = true;
+        let query = |method_name: &str| {
+            QueryBuilder::new(
+                SimpleQuery {
+                    request: QueryRequest::CallFunction {
+                        account_id: "contract.testnet".parse().unwrap(),
+                        method_name: method_name.to_owned(),
+                        args: vec![].into(),
+                    },
+                },
+                BlockReference::latest(),
+                CallResultHandler::<u8>(PhantomData),
+            )
+        };
+
+        let responses = MultiRpcBuilder::from_builders(
+            [query("first"), query("second")],
+            BlockReference::latest(),
+        )
+        .fetch_from(&network)
+        .await
+        .unwrap();
+        let values: Vec<_> = responses
+            .into_iter()
+            .map(|response| response.data)
+            .collect();
+        assert_eq!(values, [7, 7]);
+        let batches = transport
+            .requests()
+            .iter()
+            .filter(|request| request.is_array())
+            .count();
+        assert_eq!(batches, 1);
+    }
+
+    #[tokio::test]
+    async fn cache_hits_skip_throttled_endpoint() {
+        let (network, transport) = network(ScriptedTransport::responses([call_result(7u8, 5)]));
+        let network = network.with_response_cache(crate::config::ResponseCache::new(1024));
+        let query = || {
+            QueryBuilder::new(
+                SimpleQuery {
+                    request: QueryRequest::CallFunction {
+                        account_id: "contract.testnet".parse().unwrap(),
+                        method_name: "get".to_owned(),
+                        args: vec![].into(),
+                    },
+                },
+                BlockReference::BlockId(near_primitives::types::BlockId::Height(5)),
+                CallResultHandler::<u8>(PhantomData),
+            )
+        };
+
+        assert_eq!(query().fetch_from(&network).await.unwrap().data, 7);
+        network.rate_limiter.throttle(
+            &network.rpc_endpoints[0].url,
+            std::time::Duration::from_secs(60),
+        );
+        let response = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            query().fetch_from(&network),
+        )
+        .await
+        .expect("the cached response waited for the throttled endpoint");
+        assert_eq!(response.unwrap().data, 7);
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn series_skips_missing_blocks() {
+        // Returns the block height as the function call result, and no block at the odd heights above 1
+        let (network, _) = network(ScriptedTransport::new(|_, request| {
+            let height = request["params"]["block_id"].as_u64().unwrap();
+            if height > 1 && height % 2 == 1 {
+                handler_error(
+                    "UNKNOWN_BLOCK",
+                    serde_json::json!({"block_reference": {"block_id": height}}),
+                )
+            } else {
+                call_result(height, height)
+            }
+        }));
+        let request = QueryRequest::CallFunction {
+            account_id: "contract.testnet".parse().unwrap(),
+            method_name: "height".to_owned(),
+            args: vec![].into(),
+        };
+        let query = QueryBuilder::new(
+            SimpleQuery { request },
+            BlockReference::latest(),
+            CallResultHandler::<u64>(PhantomData),
+        );
+
+        let series = query
+            .series_in_range(1..=10, 3)
+            .with_concurrency(2)
+            .fetch_from(&network)
+            .await
+            .unwrap();
+        let series: Vec<_> = series
+            .into_iter()
+            .map(|(height, data)| (height, data.data))
+            .collect();
+        assert_eq!(series, [(1, 1), (4, 4), (10, 10)]);
+    }
+
+    #[test]
+    fn transaction_status_without_outcome() {
+        let response = RpcTransactionResponse {
+            final_execution_outcome: None,
+            final_execution_status: TxExecutionStatus::Included,
+        };
+        let status = RpcTransactionStatusHandler
+            .process_response(vec![response])
+            .unwrap();
+        assert_eq!(status.status, TxExecutionStatus::Included);
+        assert!(status.outcome.is_none());
+    }
+
+    /// Response to the `tx` request with the given status or error.
+    fn transaction_status(
+        response: Result<TxExecutionStatus, RpcTransactionError>,
+    ) -> crate::config::RpcResponse {
+        match response {
+            Ok(status) => rpc_result(serde_json::json!({"final_execution_status": status})),
+            Err(err) => rpc_error(near_jsonrpc_primitives::errors::RpcError::from(err)),
+        }
+    }
+
+    #[tokio::test]
+    async fn transaction_status_retries_pending_transactions() {
+        let network = |responses: [_; 2]| {
+            network(ScriptedTransport::responses(
+                responses.map(transaction_status),
+            ))
+            .0
+        };
+        let status = || {
+            crate::Transaction::status(
+                crate::types::CryptoHash::default(),
+                "alice.testnet".parse().unwrap(),
+            )
+            .wait_until(TxExecutionStatus::Included)
+        };
+
+        let response = status()
+            .fetch_from(&network([
+                Err(RpcTransactionError::TimeoutError),
+                Ok(TxExecutionStatus::Included),
+            ]))
+            .await
+            .unwrap();
+        assert_eq!(response.status, TxExecutionStatus::Included);
+        assert!(response.outcome.is_none());
+
+        let error = status()
+            .fetch_from(&network([
+                Err(RpcTransactionError::RequestRouted {
+                    transaction_hash: CryptoHash::default(),
+                }),
+                Ok(TxExecutionStatus::Included),
+            ]))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, QueryError::JsonRpcError(RetryError::Critical(_))),
+            "{error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_transaction_is_retried_until_executed() {
+        let mut executed = crate::testing::final_outcome(vec![]);
+        executed["final_execution_status"] = serde_json::json!(TxExecutionStatus::Final);
+        let (network, _) = network(ScriptedTransport::responses([
+            transaction_status(Err(RpcTransactionError::UnknownTransaction {
+                requested_transaction_hash: CryptoHash::default(),
+            })),
+            rpc_result(executed),
+        ]));
+
+        let response = crate::Transaction::status(
+            crate::types::CryptoHash::default(),
+            "alice.near".parse().unwrap(),
+        )
+        .wait_until(TxExecutionStatus::Final)
+        .fetch_from(&network)
+        .await
+        .unwrap();
+        assert_eq!(response.status, TxExecutionStatus::Final);
+        assert!(response.outcome.is_some());
+
+        // The unknown transaction is not a failure of the endpoint
+        let url = &network.rpc_endpoints[0].url;
+        assert_eq!(network.health.endpoint(url).unwrap().total_failures, 0);
+    }
+}
@@ -1,24 +1,29 @@
 use std::sync::Arc;
 
 use near_crypto::PublicKey;
+use near_jsonrpc_client::methods::send_tx::{RpcSendTransactionRequest, RpcTransactionResponse};
 use near_primitives::{
     action::delegate::SignedDelegateAction,
     transaction::SignedTransaction,
     types::{BlockHeight, Nonce},
-    views::FinalExecutionOutcomeView,
+    views::TxExecutionStatus,
 };
 use reqwest::Response;
 use tracing::{debug, info};
 
 use crate::{
-    common::utils::is_critical_transaction_error,
-    config::{retry, NetworkConfig, RetryResponse},
+    common::utils::{is_critical_transaction_error, is_endpoint_failure},
+    config::{retry, NetworkConfig, OperationKind, RetryResponse},
     errors::{
-        ExecuteMetaTransactionsError, ExecuteTransactionError, MetaSignError, SignerError,
-        ValidationError,
+        ExecuteMetaTransactionsError, ExecuteTransactionError, MetaSignError, RpcCallError,
+        SignerError, ValidationError,
     },
     signer::Signer,
-    types::{transactions::PrepopulateTransaction, CryptoHash},
+    types::{
+        outcome::TransactionOutcome,
+        transactions::{PendingTransaction, PrepopulateTransaction},
+        CryptoHash,
+    },
 };
 
 use super::{
@@ -79,6 +84,9 @@ impl From<SignedTransaction> for PrepopulateTransaction {
 pub struct ExecuteSignedTransaction {
     pub tr: TransactionableOrSigned<SignedTransaction>,
     pub signer: Arc<Signer>,
+    /// Execution status to wait for. If it's not set, [ExecuteSignedTransaction::send_to] waits for
+    /// [TxExecutionStatus::ExecutedOptimistic], and [ExecuteSignedTransaction::broadcast_to] doesn't wait at all.
+    wait_until: Option<TxExecutionStatus>,
 }
 
 impl ExecuteSignedTransaction {
@@ -86,13 +94,25 @@ impl ExecuteSignedTransaction {
         Self {
             tr: TransactionableOrSigned::Transactionable(Box::new(tr)),
             signer,
+            wait_until: None,
         }
     }
 
+    /// Sets the execution status the node waits for before responding.
+    ///
+    /// [ExecuteSignedTransaction::send_to] requires the outcome, so it fails with
+    /// [ExecuteTransactionError::MissingOutcome] on the statuses without the outcome, before the transaction is sent.
+    /// Use [ExecuteSignedTransaction::broadcast_to] for them.
+    pub const fn wait_until(mut self, status: TxExecutionStatus) -> Self {
+        self.wait_until = Some(status);
+        self
+    }
+
     pub fn meta(self) -> ExecuteMetaTransaction {
         ExecuteMetaTransaction::from_box(self.tr.transactionable(), self.signer)
     }
 
+    #[allow(clippy::result_large_err)]
     pub async fn presign_offline(
         mut self,
         public_key: PublicKey,
@@ -113,6 +133,7 @@ impl ExecuteSignedTransaction {
         Ok(self)
     }
 
+    #[allow(clippy::result_large_err)]
     pub async fn presign_with(
         self,
         network: &NetworkConfig,
@@ -132,20 +153,102 @@ impl ExecuteSignedTransaction {
         Ok(self.presign_offline(signer_key, hash, nonce).await?)
     }
 
+    #[allow(clippy::result_large_err)]
     pub async fn presign_with_mainnet(self) -> Result<Self, ExecuteTransactionError> {
         let network = NetworkConfig::mainnet();
         self.presign_with(&network).await
     }
 
+    #[allow(clippy::result_large_err)]
     pub async fn presign_with_testnet(self) -> Result<Self, ExecuteTransactionError> {
         let network = NetworkConfig::testnet();
         self.presign_with(&network).await
     }
 
+    #[allow(clippy::result_large_err)]
     pub async fn send_to(
+        self,
+        network: &NetworkConfig,
+    ) -> Result<TransactionOutcome, ExecuteTransactionError> {
+        let wait_until = self
+            .wait_until
+            .clone()
+            .unwrap_or(TxExecutionStatus::ExecutedOptimistic);
+        // The transaction sent at these statuses would end up on chain without the outcome to return
+        if matches!(
+            wait_until,
+            TxExecutionStatus::None
+                | TxExecutionStatus::Included
+                | TxExecutionStatus::IncludedFinal
+        ) {
+            return Err(ExecuteTransactionError::MissingOutcome(wait_until));
+        }
+        let started_at = std::time::Instant::now();
+        let result = self
+            .send(network, wait_until)
+            .await
+            .and_then(|(_, response)| {
+                response
+                    .final_execution_outcome
+                    .map(|outcome| outcome.into_outcome().into())
+                    .ok_or(ExecuteTransactionError::MissingOutcome(
+                        response.final_execution_status,
+                    ))
+            });
+        network.observe_operation(OperationKind::SendTransaction, started_at, result.is_ok());
+        result
+    }
+
+    /// Sends the transaction without waiting for its execution, unless [ExecuteSignedTransaction::wait_until] is set.
+    ///
+    /// The returned handle can be used to wait for the transaction later.
+    #[allow(clippy::result_large_err)]
+    pub async fn broadcast_to(
+        self,
+        network: &NetworkConfig,
+    ) -> Result<PendingTransaction, ExecuteTransactionError> {
+        let wait_until = self.wait_until.clone().unwrap_or(TxExecutionStatus::None);
+        let started_at = std::time::Instant::now();
+        let result = self.send(network, wait_until).await;
+        network.observe_operation(OperationKind::SendTransaction, started_at, result.is_ok());
+        let (signed, response) = result?;
+        Ok(PendingTransaction {
+            hash: signed.get_hash().into(),
+            sender_id: signed.transaction.signer_id().clone(),
+            status: response.final_execution_status,
+            outcome: response
+                .final_execution_outcome
+                .map(|outcome| outcome.into_outcome().into()),
+        })
+    }
+
+    #[cfg(feature = "blocking")]
+    #[allow(clippy::result_large_err)]
+    pub fn broadcast_to_blocking(
+        self,
+        network: &NetworkConfig,
+    ) -> Result<PendingTransaction, crate::errors::BlockingError<ExecuteTransactionError>> {
+        crate::blocking::block_on(self.broadcast_to(network))
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub async fn broadcast_to_mainnet(self) -> Result<PendingTransaction, ExecuteTransactionError> {
+        let network = NetworkConfig::mainnet();
+        self.broadcast_to(&network).await
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub async fn broadcast_to_testnet(self) -> Result<PendingTransaction, ExecuteTransactionError> {
+        let network = NetworkConfig::testnet();
+        self.broadcast_to(&network).await
+    }
+
+    #[allow(clippy::result_large_err)]
+    async fn send(
         mut self,
         network: &NetworkConfig,
-    ) -> Result<FinalExecutionOutcomeView, ExecuteTransactionError> {
+        wait_until: TxExecutionStatus,
+    ) -> Result<(SignedTransaction, RpcTransactionResponse), ExecuteTransactionError> {
         let (signed, transactionable) = match &mut self.tr {
             TransactionableOrSigned::Transactionable(tr) => {
                 debug!(target: TX_EXECUTOR_TARGET, "Preparing unsigned transaction");
@@ -186,54 +289,69 @@ impl ExecuteSignedTransaction {
             signed.transaction.nonce(),
         );
 
-        Self::send_impl(network, signed).await
+        let response = Self::send_impl(network, signed.clone(), wait_until).await?;
+        Ok((signed, response))
     }
 
-    pub async fn send_to_mainnet(
+    #[cfg(feature = "blocking")]
+    #[allow(clippy::result_large_err)]
+    pub fn send_to_blocking(
         self,
-    ) -> Result<FinalExecutionOutcomeView, ExecuteTransactionError> {
+        network: &NetworkConfig,
+    ) -> Result<TransactionOutcome, crate::errors::BlockingError<ExecuteTransactionError>> {
+        crate::blocking::block_on(self.send_to(network))
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub async fn send_to_mainnet(self) -> Result<TransactionOutcome, ExecuteTransactionError> {
         let network = NetworkConfig::mainnet();
         self.send_to(&network).await
     }
 
-    pub async fn send_to_testnet(
-        self,
-    ) -> Result<FinalExecutionOutcomeView, ExecuteTransactionError> {
+    #[allow(clippy::result_large_err)]
+    pub async fn send_to_testnet(self) -> Result<TransactionOutcome, ExecuteTransactionError> {
         let network = NetworkConfig::testnet();
         self.send_to(&network).await
     }
 
+    #[allow(clippy::result_large_err)]
     async fn send_impl(
         network: &NetworkConfig,
         signed_tr: SignedTransaction,
-    ) -> Result<FinalExecutionOutcomeView, ExecuteTransactionError> {
-        retry(network.clone(), |json_rpc_client| {
+        wait_until: TxExecutionStatus,
+    ) -> Result<RpcTransactionResponse, ExecuteTransactionError> {
+        retry(network.clone(), |rpc_client| {
             let signed_tr = signed_tr.clone();
+            let wait_until = wait_until.clone();
             async move {
-                    let result = match json_rpc_client
-                .call(
-                    near_jsonrpc_client::methods::broadcast_tx_commit::RpcBroadcastTxCommitRequest {
+                let result = match rpc_client
+                    .call(RpcSendTransactionRequest {
                         signed_transaction: signed_tr.clone(),
-                        },
-                    )
-                        .await
-                    {
-                        Ok(result) => RetryResponse::Ok(result),
-                        Err(err) if is_critical_transaction_error(&err) => RetryResponse::Critical(err),
-                        Err(err) => RetryResponse::Retry(err),
-                    };
-
-                    tracing::debug!(
-                        target: TX_EXECUTOR_TARGET,
-                        "Broadcasting transaction {} resulted in {:?}",
-                        signed_tr.get_hash(),
-                        result
-                    );
-
+                        wait_until,
+                    })
+                    .await
+                {
+                    Ok(result) => RetryResponse::Ok(result),
+                    Err(RpcCallError::TransportError(err)) => RetryResponse::Transport(err),
+                    Err(RpcCallError::JsonRpcError(err)) if is_critical_transaction_error(&err) => {
+                        RetryResponse::Critical(err)
+                    }
+                    Err(RpcCallError::JsonRpcError(err)) if !is_endpoint_failure(&err) => {
+                        RetryResponse::Pending(err)
+                    }
+                    Err(RpcCallError::JsonRpcError(err)) => RetryResponse::Retry(err),
+                };
+
+                tracing::debug!(
+                    target: TX_EXECUTOR_TARGET,
+                    "Broadcasting transaction {} resulted in {:?}",
+                    signed_tr.get_hash(),
                     result
-                }
-            },
-        )
+                );
+
+                result
+            }
+        })
         .await
         .map_err(ExecuteTransactionError::TransactionError)
     }
@@ -267,6 +385,7 @@ impl ExecuteMetaTransaction {
         self
     }
 
+    #[allow(clippy::result_large_err)]
     pub async fn presign_offline(
         mut self,
         signer_key: PublicKey,
@@ -293,13 +412,13 @@ impl ExecuteMetaTransaction {
                 block_hash,
                 max_block_height,
             )
-            .await
-            .map_err(MetaSignError::from)?;
+            .await?;
 
         self.tr = TransactionableOrSigned::Signed((signed_tr, self.tr.transactionable()));
         Ok(self)
     }
 
+    #[allow(clippy::result_large_err)]
     pub async fn presign_with(
         self,
         network: &NetworkConfig,
@@ -327,16 +446,19 @@ impl ExecuteMetaTransaction {
             .await
     }
 
+    #[allow(clippy::result_large_err)]
     pub async fn presign_with_mainnet(self) -> Result<Self, ExecuteMetaTransactionsError> {
         let network = NetworkConfig::mainnet();
         self.presign_with(&network).await
     }
 
+    #[allow(clippy::result_large_err)]
     pub async fn presign_with_testnet(self) -> Result<Self, ExecuteMetaTransactionsError> {
         let network = NetworkConfig::testnet();
         self.presign_with(&network).await
     }
 
+    #[allow(clippy::result_large_err)]
     pub async fn send_to(
         mut self,
         network: &NetworkConfig,
@@ -384,21 +506,32 @@ impl ExecuteMetaTransaction {
         Self::send_impl(network, signed).await
     }
 
+    #[cfg(feature = "blocking")]
+    #[allow(clippy::result_large_err)]
+    pub fn send_to_blocking(
+        self,
+        network: &NetworkConfig,
+    ) -> Result<Response, crate::errors::BlockingError<ExecuteMetaTransactionsError>> {
+        crate::blocking::block_on(self.send_to(network))
+    }
+
+    #[allow(clippy::result_large_err)]
     pub async fn send_to_mainnet(self) -> Result<reqwest::Response, ExecuteMetaTransactionsError> {
         let network = NetworkConfig::mainnet();
         self.send_to(&network).await
     }
 
+    #[allow(clippy::result_large_err)]
     pub async fn send_to_testnet(self) -> Result<reqwest::Response, ExecuteMetaTransactionsError> {
         let network = NetworkConfig::testnet();
         self.send_to(&network).await
     }
 
+    #[allow(clippy::result_large_err)]
     async fn send_impl(
         network: &NetworkConfig,
         tr: SignedDelegateAction,
     ) -> Result<reqwest::Response, ExecuteMetaTransactionsError> {
-        let client = reqwest::Client::new();
         let json_payload = serde_json::json!({
             "signed_delegate_action": SignedDelegateActionAsBase64::from(
                 tr.clone()
@@ -409,7 +542,8 @@ impl ExecuteMetaTransaction {
             "Sending meta transaction to relayer. Payload: {:?}",
             json_payload
         );
-        let resp = client
+        let resp = network
+            .http_client
             .post(
                 network
                     .meta_transaction_relayer_url
@@ -430,3 +564,86 @@ impl ExecuteMetaTransaction {
         Ok(resp)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use near_crypto::{KeyType, SecretKey};
+    use serde_json::json;
+
+    use crate::{
+        testing::{final_outcome, network, rpc_result, ScriptedTransport},
+        Transaction,
+    };
+
+    use super::*;
+
+    async fn signed_transaction() -> ExecuteSignedTransaction {
+        let secret_key = SecretKey::from_seed(KeyType::ED25519, "test");
+        let public_key = secret_key.public_key();
+        let signer = Signer::new(Signer::secret_key(secret_key)).unwrap();
+        Transaction::construct(
+            "alice.testnet".parse().unwrap(),
+            "bob.testnet".parse().unwrap(),
+        )
+        .with_signer(signer)
+        .presign_offline(public_key, CryptoHash::default(), 1)
+        .await
+        .unwrap()
+    }
+
+    fn executed(status: &str) -> serde_json::Value {
+        let mut outcome = final_outcome(vec![]);
+        outcome["final_execution_status"] = status.into();
+        outcome
+    }
+
+    #[tokio::test]
+    async fn broadcast_returns_pending_transaction() {
+        let (network, transport) = network(ScriptedTransport::responses([
+            rpc_result(json!({"final_execution_status": "NONE"})),
+            rpc_result(executed("FINAL")),
+        ]));
+
+        let pending = signed_transaction()
+            .await
+            .broadcast_to(&network)
+            .await
+            .unwrap();
+        assert_eq!(pending.status, TxExecutionStatus::None);
+        assert!(pending.outcome.is_none());
+        assert_eq!(pending.sender_id, "alice.testnet");
+
+        let outcome = pending.wait_for_final(&network).await.unwrap();
+        assert!(outcome.is_success());
+
+        let requests = transport.requests();
+        assert_eq!(requests[0]["method"], "send_tx");
+        assert_eq!(requests[0]["params"]["wait_until"], "NONE");
+        assert_eq!(requests[1]["method"], "tx");
+        assert_eq!(requests[1]["params"]["wait_until"], "FINAL");
+        assert_eq!(
+            requests[1]["params"]["tx_hash"],
+            pending.hash.to_string().as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn send_requires_outcome() {
+        let (network, transport) = network(ScriptedTransport::default());
+
+        let error = signed_transaction()
+            .await
+            .wait_until(TxExecutionStatus::Included)
+            .send_to(&network)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                error,
+                ExecuteTransactionError::MissingOutcome(TxExecutionStatus::Included)
+            ),
+            "{error:?}"
+        );
+        assert!(transport.requests().is_empty());
+    }
+}
@@ -61,6 +61,77 @@ pub fn is_critical_blocks_error(
     })
 }
 
+pub fn is_critical_protocol_config_error(
+    err: &near_jsonrpc_client::errors::JsonRpcError<
+        near_jsonrpc_primitives::types::config::RpcProtocolConfigError,
+    >,
+) -> bool {
+    is_critical_json_rpc_error(err, |err| match err {
+        near_jsonrpc_primitives::types::config::RpcProtocolConfigError::UnknownBlock { .. }
+        | near_jsonrpc_primitives::types::config::RpcProtocolConfigError::InternalError {
+            ..
+        } => true,
+    })
+}
+
+pub fn is_critical_chunk_error(
+    err: &near_jsonrpc_client::errors::JsonRpcError<
+        near_jsonrpc_primitives::types::chunks::RpcChunkError,
+    >,
+) -> bool {
+    is_critical_json_rpc_error(err, |err| match err {
+        near_jsonrpc_primitives::types::chunks::RpcChunkError::UnknownBlock { .. }
+        | near_jsonrpc_primitives::types::chunks::RpcChunkError::InvalidShardId { .. }
+        | near_jsonrpc_primitives::types::chunks::RpcChunkError::UnknownChunk { .. }
+        | near_jsonrpc_primitives::types::chunks::RpcChunkError::InternalError { .. } => true,
+    })
+}
+
+pub fn is_critical_status_error(
+    err: &near_jsonrpc_client::errors::JsonRpcError<
+        near_jsonrpc_primitives::types::status::RpcStatusError,
+    >,
+) -> bool {
+    is_critical_json_rpc_error(err, |err| match err {
+        // The other node could be synced
+        near_jsonrpc_primitives::types::status::RpcStatusError::NodeIsSyncing
+        | near_jsonrpc_primitives::types::status::RpcStatusError::NoNewBlocks { .. } => false,
+        near_jsonrpc_primitives::types::status::RpcStatusError::EpochOutOfBounds { .. }
+        | near_jsonrpc_primitives::types::status::RpcStatusError::InternalError { .. } => true,
+    })
+}
+
+pub fn is_critical_genesis_config_error(
+    err: &near_jsonrpc_client::errors::JsonRpcError<
+        near_jsonrpc_client::methods::EXPERIMENTAL_genesis_config::RpcGenesisConfigError,
+    >,
+) -> bool {
+    is_critical_json_rpc_error(err, |_| true)
+}
+
+pub fn is_critical_gas_price_error(
+    err: &near_jsonrpc_client::errors::JsonRpcError<
+        near_jsonrpc_primitives::types::gas_price::RpcGasPriceError,
+    >,
+) -> bool {
+    is_critical_json_rpc_error(err, |err| match err {
+        near_jsonrpc_primitives::types::gas_price::RpcGasPriceError::UnknownBlock { .. }
+        | near_jsonrpc_primitives::types::gas_price::RpcGasPriceError::InternalError { .. } => true,
+    })
+}
+
+pub fn is_critical_network_info_error(
+    err: &near_jsonrpc_client::errors::JsonRpcError<
+        near_jsonrpc_primitives::types::network_info::RpcNetworkInfoError,
+    >,
+) -> bool {
+    is_critical_json_rpc_error(err, |err| match err {
+        near_jsonrpc_primitives::types::network_info::RpcNetworkInfoError::InternalError {
+            ..
+        } => true,
+    })
+}
+
 pub fn is_critical_validator_error(
     err: &near_jsonrpc_client::errors::JsonRpcError<
         near_jsonrpc_primitives::types::validator::RpcValidatorError,
@@ -116,6 +187,50 @@ pub fn is_critical_transaction_error(
     })
 }
 
+/// Unlike the broadcast, the lookup is retried while the transaction is unknown, not executed up to the requested status,
+/// or the node doesn't track the sender shard.
+pub fn is_critical_transaction_status_error(
+    err: &near_jsonrpc_client::errors::JsonRpcError<
+        near_jsonrpc_client::methods::tx::RpcTransactionError,
+    >,
+) -> bool {
+    is_critical_json_rpc_error(err, |err| match err {
+        near_jsonrpc_client::methods::tx::RpcTransactionError::TimeoutError
+        | near_jsonrpc_client::methods::tx::RpcTransactionError::DoesNotTrackShard
+        | near_jsonrpc_client::methods::tx::RpcTransactionError::UnknownTransaction { .. }
+        | near_jsonrpc_client::methods::tx::RpcTransactionError::InternalError { .. } => false,
+        near_jsonrpc_client::methods::tx::RpcTransactionError::InvalidTransaction { .. }
+        | near_jsonrpc_client::methods::tx::RpcTransactionError::RequestRouted { .. } => true,
+    })
+}
+
+/// Returns `true` if the retryable error is the failure of the endpoint itself:
+/// a transport error, `5xx`, `408` or `429` response.
+///
+/// Other retryable errors, e.g. the transaction that isn't executed yet, are [RetryResponse::Pending](crate::config::RetryResponse::Pending),
+/// so they don't count against the endpoint health.
+pub fn is_endpoint_failure<T>(err: &near_jsonrpc_client::errors::JsonRpcError<T>) -> bool {
+    match err {
+        near_jsonrpc_client::errors::JsonRpcError::TransportError(_)
+        | near_jsonrpc_client::errors::JsonRpcError::ServerError(
+            near_jsonrpc_client::errors::JsonRpcServerError::InternalError { .. }
+            | near_jsonrpc_client::errors::JsonRpcServerError::ResponseStatusError(
+                near_jsonrpc_client::errors::JsonRpcServerResponseStatusError::TimeoutError
+                | near_jsonrpc_client::errors::JsonRpcServerResponseStatusError::ServiceUnavailable
+                | near_jsonrpc_client::errors::JsonRpcServerResponseStatusError::TooManyRequests,
+            ),
+        ) => true,
+        near_jsonrpc_client::errors::JsonRpcError::ServerError(
+            near_jsonrpc_client::errors::JsonRpcServerError::ResponseStatusError(
+                near_jsonrpc_client::errors::JsonRpcServerResponseStatusError::Unexpected {
+                    status,
+                },
+            ),
+        ) => status.is_server_error(),
+        near_jsonrpc_client::errors::JsonRpcError::ServerError(_) => false,
+    }
+}
+
 fn is_critical_json_rpc_error<T>(
     err: &near_jsonrpc_client::errors::JsonRpcError<T>,
     is_critical_t: impl Fn(&T) -> bool,
@@ -0,0 +1,373 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use near_primitives::types::BlockHeight;
+use tracing::trace;
+use url::Url;
+
+const CACHE_TARGET: &str = "near_api::rpc::cache";
+
+/// How long the responses for the latest blocks are cached by default.
+const DEFAULT_TTL: Duration = Duration::from_secs(1);
+
+/// Size-bounded cache of the successful `query`, `block` and `validators` responses.
+///
+/// Requests pinned to the block hash or epoch id are immutable, so they are cached until evicted.
+/// A block height is immutable only once it's final: the cache remembers the height of the latest `final` block
+/// it has seen from each endpoint, and the requests at the heights above it are treated as the latest ones.
+/// Requests at the `optimistic` or `final` block move with the chain, so they are cached only for [ResponseCache::with_ttl].
+/// When the total size of the cached responses exceeds the limit, the least recently used ones are evicted.
+///
+/// The cache is shared between the clones, so it can be attached to several [NetworkConfig](super::NetworkConfig)s.
+/// The responses are keyed by the endpoint URL, so the networks sharing the cache never see each other's responses.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    max_size: usize,
+    ttl: Duration,
+    entries: Arc<Mutex<Entries>>,
+}
+
+/// The endpoint URL, the method and the params of the request.
+type Key = (String, String, String);
+
+#[derive(Debug, Default)]
+struct Entries {
+    map: HashMap<Key, Entry>,
+    /// Keys ordered by the last use, the least recently used first.
+    usage: BTreeMap<u64, Key>,
+    /// Height of the latest `final` block seen from each endpoint.
+    final_heights: HashMap<String, BlockHeight>,
+    size: usize,
+    tick: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    body: Arc<[u8]>,
+    expires_at: Option<Instant>,
+    last_used: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lifetime {
+    Immutable,
+    /// Immutable once the block at the height is final.
+    Height(BlockHeight),
+    Latest,
+}
+
+impl ResponseCache {
+    /// Creates the cache that holds up to `max_size` bytes of responses.
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            ttl: DEFAULT_TTL,
+            entries: Default::default(),
+        }
+    }
+
+    /// Sets how long the responses at the `optimistic` and `final` blocks are cached. Zero disables caching of them.
+    pub const fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Number of the cached responses, including the expired ones that weren't evicted yet.
+    pub fn len(&self) -> usize {
+        self.lock().map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Total size of the cached responses in bytes.
+    pub fn size(&self) -> usize {
+        self.lock().size
+    }
+
+    pub fn clear(&self) {
+        *self.lock() = Entries::default();
+    }
+
+    /// Returns the cached response body for the JSON-RPC request.
+    pub(crate) fn get(&self, endpoint: &Url, request: &serde_json::Value) -> Option<Arc<[u8]>> {
+        lifetime(request)?;
+        let key = key(endpoint, request);
+        let mut entries = self.lock();
+        let entry = entries.map.get(&key)?;
+        if entry
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Instant::now())
+        {
+            entries.remove(&key);
+            return None;
+        }
+        let body = entry.body.clone();
+        entries.touch(&key);
+        drop(entries);
+
+        trace!(target: CACHE_TARGET, "Cache hit for <{}> with params {} at {}", key.1, key.2, key.0);
+        Some(body)
+    }
+
+    /// Caches the successful response body of the JSON-RPC request, if the request is cacheable.
+    pub(crate) fn insert(&self, endpoint: &Url, request: &serde_json::Value, body: &[u8]) {
+        let Some(lifetime) = lifetime(request) else {
+            return;
+        };
+        let key = key(endpoint, request);
+        let mut entries = self.lock();
+        if let Some(height) = final_height(request, body) {
+            let final_height = entries.final_heights.entry(key.0.clone()).or_default();
+            *final_height = (*final_height).max(height);
+        }
+
+        let expires_at = match lifetime {
+            Lifetime::Immutable => None,
+            Lifetime::Height(height)
+                if entries
+                    .final_heights
+                    .get(&key.0)
+                    .is_some_and(|final_height| height <= *final_height) =>
+            {
+                None
+            }
+            _ if !self.ttl.is_zero() => Some(Instant::now() + self.ttl),
+            _ => return,
+        };
+        if body.len() > self.max_size {
+            return;
+        }
+
+        entries.remove(&key);
+        while entries.size + body.len() > self.max_size {
+            entries.evict_least_recently_used();
+        }
+        entries.tick += 1;
+        let last_used = entries.tick;
+        entries.size += body.len();
+        entries.usage.insert(last_used, key.clone());
+        entries.map.insert(
+            key,
+            Entry {
+                body: body.into(),
+                expires_at,
+                last_used,
+            },
+        );
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Entries {
+    fn remove(&mut self, key: &Key) {
+        if let Some(entry) = self.map.remove(key) {
+            self.usage.remove(&entry.last_used);
+            self.size -= entry.body.len();
+        }
+    }
+
+    fn touch(&mut self, key: &Key) {
+        self.tick += 1;
+        let tick = self.tick;
+        if let Some(entry) = self.map.get_mut(key) {
+            self.usage.remove(&entry.last_used);
+            entry.last_used = tick;
+            self.usage.insert(tick, key.clone());
+        }
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        if let Some((_, key)) = self.usage.pop_first() {
+            if let Some(entry) = self.map.remove(&key) {
+                self.size -= entry.body.len();
+            }
+        }
+    }
+}
+
+fn key(endpoint: &Url, request: &serde_json::Value) -> Key {
+    (
+        endpoint.to_string(),
+        request["method"].as_str().unwrap_or_default().to_string(),
+        request["params"].to_string(),
+    )
+}
+
+/// Returns how long the response to the request stays valid, or `None` if it shouldn't be cached.
+fn lifetime(request: &serde_json::Value) -> Option<Lifetime> {
+    if !matches!(
+        request["method"].as_str(),
+        Some("query" | "block" | "validators")
+    ) {
+        return None;
+    }
+
+    let params = request["params"].as_object()?;
+    if let Some(block_id) = params.get("block_id") {
+        Some(
+            block_id
+                .as_u64()
+                .map_or(Lifetime::Immutable, Lifetime::Height),
+        )
+    } else if params.contains_key("epoch_id") {
+        Some(Lifetime::Immutable)
+    } else if params.contains_key("finality") || params.contains_key("latest") {
+        Some(Lifetime::Latest)
+    } else {
+        match params.get("sync_checkpoint")?.as_str()? {
+            "genesis" => Some(Lifetime::Immutable),
+            _ => Some(Lifetime::Latest),
+        }
+    }
+}
+
+/// Returns the height of the block the response to the `final` request is at.
+fn final_height(request: &serde_json::Value, body: &[u8]) -> Option<BlockHeight> {
+    if request["params"]["finality"].as_str() != Some("final") {
+        return None;
+    }
+    let response: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let result = &response["result"];
+    result["header"]["height"]
+        .as_u64()
+        .or_else(|| result["block_height"].as_u64())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::testing::HASH;
+
+    fn mainnet() -> Url {
+        "https://rpc.mainnet.near.org".parse().unwrap()
+    }
+
+    fn block_request(params: serde_json::Value) -> serde_json::Value {
+        json!({"jsonrpc": "2.0", "id": "dontcare", "method": "block", "params": params})
+    }
+
+    #[test]
+    fn lifetime_depends_on_reference() {
+        assert_eq!(
+            lifetime(&block_request(json!({"block_id": 10}))),
+            Some(Lifetime::Height(10))
+        );
+        assert_eq!(
+            lifetime(&block_request(json!({"block_id": HASH}))),
+            Some(Lifetime::Immutable)
+        );
+        assert_eq!(
+            lifetime(&block_request(json!({"finality": "optimistic"}))),
+            Some(Lifetime::Latest)
+        );
+        assert_eq!(
+            lifetime(&block_request(json!({"sync_checkpoint": "genesis"}))),
+            Some(Lifetime::Immutable)
+        );
+        assert_eq!(
+            lifetime(&json!({"method": "validators", "params": {"latest": null}})),
+            Some(Lifetime::Latest)
+        );
+        assert_eq!(
+            lifetime(&json!({"method": "gas_price", "params": [null]})),
+            None
+        );
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = ResponseCache::new(8);
+        let (first, second, third) = (
+            block_request(json!({"block_id": 1})),
+            block_request(json!({"block_id": 2})),
+            block_request(json!({"block_id": 3})),
+        );
+        cache.insert(&mainnet(), &first, b"1111");
+        cache.insert(&mainnet(), &second, b"2222");
+        assert!(cache.get(&mainnet(), &first).is_some());
+
+        cache.insert(&mainnet(), &third, b"3333");
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.size(), 8);
+        assert!(cache.get(&mainnet(), &second).is_none());
+        assert_eq!(cache.get(&mainnet(), &first).as_deref(), Some(&b"1111"[..]));
+        assert_eq!(cache.get(&mainnet(), &third).as_deref(), Some(&b"3333"[..]));
+
+        let large = block_request(json!({"block_id": 4}));
+        cache.insert(&mainnet(), &large, b"too large to be cached");
+        assert!(cache.get(&mainnet(), &large).is_none());
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn latest_responses_expire() {
+        let request = block_request(json!({"finality": "final"}));
+
+        let cache = ResponseCache::new(1024).with_ttl(Duration::ZERO);
+        cache.insert(&mainnet(), &request, b"block");
+        assert!(cache.is_empty());
+
+        let cache = ResponseCache::new(1024).with_ttl(Duration::from_millis(10));
+        cache.insert(&mainnet(), &request, b"block");
+        assert!(cache.get(&mainnet(), &request).is_some());
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(cache.get(&mainnet(), &request).is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn heights_are_immutable_once_final() {
+        let testnet: Url = "https://rpc.testnet.near.org".parse().unwrap();
+        let cache = ResponseCache::new(1024).with_ttl(Duration::ZERO);
+        let at_height = |height: u64| block_request(json!({"block_id": height}));
+
+        cache.insert(&mainnet(), &at_height(10), b"10");
+        assert!(cache.is_empty());
+
+        let final_block = json!({"result": {"header": {"height": 10}}}).to_string();
+        cache.insert(
+            &mainnet(),
+            &block_request(json!({"finality": "final"})),
+            final_block.as_bytes(),
+        );
+        cache.insert(&mainnet(), &at_height(10), b"10");
+        cache.insert(&mainnet(), &at_height(11), b"11");
+        cache.insert(&testnet, &at_height(10), b"10");
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(&mainnet(), &at_height(10)).is_some());
+    }
+
+    #[test]
+    fn networks_dont_share_responses() {
+        let testnet: Url = "https://rpc.testnet.near.org".parse().unwrap();
+        let request = block_request(json!({"block_id": 1}));
+        let cache = ResponseCache::new(1024);
+        let (mainnet_cache, testnet_cache) = (cache.clone(), cache);
+
+        mainnet_cache.insert(&mainnet(), &request, b"mainnet");
+        assert!(testnet_cache.get(&testnet, &request).is_none());
+
+        testnet_cache.insert(&testnet, &request, b"testnet");
+        assert_eq!(testnet_cache.len(), 2);
+        assert_eq!(
+            mainnet_cache.get(&mainnet(), &request).as_deref(),
+            Some(&b"mainnet"[..])
+        );
+        assert_eq!(
+            testnet_cache.get(&testnet, &request).as_deref(),
+            Some(&b"testnet"[..])
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tracing::{debug, error};
+
+use crate::errors::{CassetteError, TransportError};
+
+use super::{RPCEndpoint, RpcResponse, RpcTransport};
+
+const CASSETTE_TARGET: &str = "near_api::rpc::cassette";
+
+/// Recorded JSON-RPC traffic.
+///
+/// The requests are matched by the method and params, while the request id and the endpoint are ignored,
+/// so the cassette can be replayed with any endpoint configuration.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct Cassette {
+    pub interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Interaction {
+    pub endpoint: url::Url,
+    pub method: String,
+    pub params: serde_json::Value,
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<Duration>,
+    /// JSON-RPC response message, or a string if the body isn't a valid JSON.
+    pub response: serde_json::Value,
+}
+
+impl Cassette {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CassetteError> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CassetteError> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+fn request_key(request: &serde_json::Value) -> (String, String) {
+    let (method, params) = method_and_params(request);
+    (method, params.to_string())
+}
+
+/// Batch requests are recorded as the `batch` method with the list of the requests as params.
+fn method_and_params(request: &serde_json::Value) -> (String, serde_json::Value) {
+    request.as_array().map_or_else(
+        || {
+            (
+                request["method"].as_str().unwrap_or_default().to_string(),
+                request["params"].clone(),
+            )
+        },
+        |batch| {
+            (
+                "batch".to_string(),
+                batch
+                    .iter()
+                    .map(|request| {
+                        serde_json::json!({"method": request["method"], "params": request["params"]})
+                    })
+                    .collect(),
+            )
+        },
+    )
+}
+
+/// [RpcTransport] that sends the requests through the inner transport
+/// and records every response into the cassette.
+///
+/// The interactions are kept in memory and written to the cassette file by [RecordingTransport::save],
+/// or when the last clone of the transport is dropped.
+/// Keep a clone of the transport to save the cassette at the known point, e.g. at the end of the test.
+#[derive(Debug, Clone)]
+pub struct RecordingTransport {
+    inner: Arc<dyn RpcTransport>,
+    recording: Arc<Recording>,
+}
+
+#[derive(Debug)]
+struct Recording {
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+}
+
+impl RecordingTransport {
+    /// Starts a new cassette at the given path, overwriting the existing one.
+    pub fn new(inner: Arc<dyn RpcTransport>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            recording: Arc::new(Recording {
+                path: path.into(),
+                cassette: Mutex::new(Cassette::default()),
+            }),
+        }
+    }
+
+    /// Writes the interactions recorded so far to the cassette file.
+    ///
+    /// The file is written with blocking I/O.
+    pub fn save(&self) -> Result<(), CassetteError> {
+        self.recording.save()
+    }
+}
+
+impl Recording {
+    fn save(&self) -> Result<(), CassetteError> {
+        let cassette = self.lock().clone();
+        cassette.save(&self.path)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cassette> {
+        self.cassette
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        if let Err(err) = self.save() {
+            error!(
+                target: CASSETTE_TARGET,
+                "Failed to save the cassette to {}: {err}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RpcTransport for RecordingTransport {
+    async fn send(
+        &self,
+        endpoint: &RPCEndpoint,
+        request: &serde_json::Value,
+    ) -> Result<RpcResponse, TransportError> {
+        let response = self.inner.send(endpoint, request).await?;
+
+        let (method, params) = method_and_params(request);
+        debug!(target: CASSETTE_TARGET, "Recording <{method}> response from {}", endpoint.url);
+        let interaction = Interaction {
+            endpoint: endpoint.url.clone(),
+            method,
+            params,
+            status: response.status.as_u16(),
+            retry_after: response.retry_after,
+            response: serde_json::from_slice(&response.body).unwrap_or_else(|_| {
+                serde_json::Value::String(String::from_utf8_lossy(&response.body).into_owned())
+            }),
+        };
+        self.recording.lock().interactions.push(interaction);
+
+        Ok(response)
+    }
+
+    fn with_http_client(&self, client: reqwest::Client) -> Option<Arc<dyn RpcTransport>> {
+        Some(Arc::new(Self {
+            inner: self.inner.with_http_client(client)?,
+            recording: self.recording.clone(),
+        }))
+    }
+}
+
+/// [RpcTransport] that serves the responses from the cassette without touching the network.
+///
+/// Identical requests are served with the recorded responses in the recorded order.
+/// Requests that are not recorded, or were already served as many times as recorded,
+/// fail with [CassetteError::UnrecordedRequest].
+#[derive(Debug)]
+pub struct ReplayTransport {
+    responses: Mutex<HashMap<(String, String), VecDeque<Interaction>>>,
+}
+
+impl ReplayTransport {
+    pub fn new(cassette: Cassette) -> Self {
+        let mut responses: HashMap<_, VecDeque<_>> = HashMap::new();
+        for interaction in cassette.interactions {
+            let key = (interaction.method.clone(), interaction.params.to_string());
+            responses.entry(key).or_default().push_back(interaction);
+        }
+        Self {
+            responses: Mutex::new(responses),
+        }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, CassetteError> {
+        Ok(Self::new(Cassette::load(path)?))
+    }
+}
+
+#[async_trait::async_trait]
+impl RpcTransport for ReplayTransport {
+    async fn send(
+        &self,
+        _endpoint: &RPCEndpoint,
+        request: &serde_json::Value,
+    ) -> Result<RpcResponse, TransportError> {
+        let key = request_key(request);
+        let interaction = self
+            .responses
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get_mut(&key)
+            .and_then(VecDeque::pop_front);
+        let Some(interaction) = interaction else {
+            let (method, params) = key;
+            error!(
+                target: CASSETTE_TARGET,
+                "Request <{method}> with params {params} is not recorded in the cassette"
+            );
+            return Err(TransportError::Cassette(CassetteError::UnrecordedRequest {
+                method,
+                params,
+            }));
+        };
+
+        let body = match interaction.response {
+            serde_json::Value::String(body) => body.into_bytes(),
+            response => response.to_string().into_bytes(),
+        };
+        Ok(RpcResponse {
+            status: reqwest::StatusCode::from_u16(interaction.status)
+                .map_err(|err| TransportError::Other(Box::new(err)))?,
+            retry_after: interaction.retry_after,
+            body,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::ScriptedTransport;
+
+    use super::*;
+
+    fn echo() -> Arc<ScriptedTransport> {
+        Arc::new(ScriptedTransport::new(|_, request| {
+            RpcResponse::ok(request["params"].to_string())
+        }))
+    }
+
+    fn request(params: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({"jsonrpc": "2.0", "id": "random", "method": "query", "params": params})
+    }
+
+    #[tokio::test]
+    async fn record_and_replay() {
+        let path =
+            std::env::temp_dir().join(format!("near-api-cassette-{}.json", std::process::id()));
+        let endpoint = RPCEndpoint::testnet();
+
+        let recorder = RecordingTransport::new(echo(), &path);
+        for params in [serde_json::json!({"a": 1}), serde_json::json!({"a": 2})] {
+            recorder.send(&endpoint, &request(params)).await.unwrap();
+        }
+        drop(recorder);
+
+        let replay = ReplayTransport::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let response = replay
+            .send(
+                &RPCEndpoint::mainnet(),
+                &request(serde_json::json!({"a": 2})),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.body, br#"{"a":2}"#);
+
+        let error = replay
+            .send(&endpoint, &request(serde_json::json!({"a": 2})))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            TransportError::Cassette(CassetteError::UnrecordedRequest { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn replay_miss_is_reported() {
+        let network = crate::config::NetworkConfig::testnet()
+            .with_transport(ReplayTransport::new(Cassette::default()));
+
+        let error = crate::Chain::block_number()
+            .fetch_from(&network)
+            .await
+            .unwrap_err();
+        let crate::errors::QueryError::JsonRpcError(crate::errors::RetryError::Transport(
+            TransportError::Cassette(CassetteError::UnrecordedRequest { method, .. }),
+        )) = &error
+        else {
+            panic!("expected cassette error, got {error:?}");
+        };
+        assert_eq!(method, "block");
+        assert!(!error.to_string().contains("serializing"), "{error}");
+    }
+}
@@ -0,0 +1,485 @@
+use std::{sync::Arc, time::Duration};
+
+use near_jsonrpc_client::{
+    errors::{
+        JsonRpcError, JsonRpcServerError, JsonRpcServerResponseStatusError,
+        JsonRpcTransportHandlerResponseError, JsonRpcTransportRecvError, JsonRpcTransportSendError,
+        RpcTransportError,
+    },
+    methods::{self, RpcMethod},
+    MethodCallResult,
+};
+use reqwest::StatusCode;
+use tracing::debug;
+
+use crate::errors::{BatchError, RpcCallError, TransportError};
+
+use super::{
+    InFlightRequests, RPCEndpoint, RateLimiter, ResponseCache, RpcCallEvent, RpcErrorKind,
+    RpcObserver, RpcTransport,
+};
+
+const RPC_CLIENT_TARGET: &str = "near_api::rpc::client";
+
+type CallResult<M> = Result<<M as RpcMethod>::Response, RpcCallError<<M as RpcMethod>::Error>>;
+
+/// How long the endpoint is paused after `429 Too Many Requests` without the `Retry-After` header.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+/// Upper bound of the `Retry-After` if the [RPCEndpoint::max_sleep] isn't set.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// JSON-RPC client of the single endpoint.
+///
+/// It mirrors [near_jsonrpc_client::JsonRpcClient::call], but sends the requests through the [RpcTransport],
+/// reports `429 Too Many Requests` with its `Retry-After` to the [RateLimiter]
+/// and stores the successful responses in the [ResponseCache].
+/// The cache is looked up before the retries with [NetworkConfig::cached_response](super::NetworkConfig::cached_response).
+///
+/// The failures of the custom transport and the cassette misses are returned as [RpcCallError::TransportError],
+/// as the JSON-RPC errors can't carry them.
+#[derive(Debug, Clone)]
+pub struct RpcClient {
+    endpoint: RPCEndpoint,
+    transport: Arc<dyn RpcTransport>,
+    rate_limiter: RateLimiter,
+    observer: Option<Arc<dyn RpcObserver>>,
+    cache: Option<ResponseCache>,
+    in_flight: InFlightRequests,
+}
+
+impl RpcClient {
+    pub(crate) fn new(
+        endpoint: RPCEndpoint,
+        transport: Arc<dyn RpcTransport>,
+        rate_limiter: RateLimiter,
+        observer: Option<Arc<dyn RpcObserver>>,
+        cache: Option<ResponseCache>,
+        in_flight: InFlightRequests,
+    ) -> Self {
+        Self {
+            endpoint,
+            transport,
+            rate_limiter,
+            observer,
+            cache,
+            in_flight,
+        }
+    }
+
+    // The errors are the ones of the JSON-RPC client, which are not boxed
+    #[allow(clippy::result_large_err)]
+    pub async fn call<M>(&self, method: M) -> CallResult<M>
+    where
+        M: RpcMethod,
+    {
+        self.call_with(method, false).await
+    }
+
+    /// Same as [RpcClient::call], but the concurrent identical requests to the endpoint share one network call.
+    ///
+    /// It should be used only for the view requests, as the response is not guaranteed to be fresh.
+    #[allow(clippy::result_large_err)]
+    pub async fn call_coalesced<M>(&self, method: M) -> CallResult<M>
+    where
+        M: RpcMethod,
+    {
+        self.call_with(method, true).await
+    }
+
+    /// Sends the requests as one JSON-RPC batch and returns their results in the same order.
+    ///
+    /// Fails with [BatchError::Rejected] if the endpoint doesn't support batches.
+    /// The batches bypass the [ResponseCache].
+    #[allow(clippy::result_large_err)]
+    pub async fn call_batch<M>(
+        &self,
+        methods: &[M],
+    ) -> Result<Vec<MethodCallResult<M::Response, M::Error>>, BatchError<M::Error>>
+    where
+        M: RpcMethod + Sync,
+    {
+        let started_at = std::time::Instant::now();
+        let result = self.send_batch(methods).await;
+        if let Some(observer) = &self.observer {
+            observer.on_rpc_call(&RpcCallEvent {
+                endpoint: &self.endpoint.url,
+                method: "batch",
+                latency: started_at.elapsed(),
+                error: result.as_ref().err().map(|err| match err {
+                    BatchError::Rejected(_) => RpcErrorKind::RequestValidation,
+                    BatchError::JsonRpcError(err) => RpcErrorKind::from(err),
+                    BatchError::TransportError(_) => RpcErrorKind::Transport,
+                }),
+            });
+        }
+        result
+    }
+
+    pub const fn endpoint(&self) -> &RPCEndpoint {
+        &self.endpoint
+    }
+
+    #[allow(clippy::result_large_err)]
+    async fn send_batch<M>(
+        &self,
+        methods: &[M],
+    ) -> Result<Vec<MethodCallResult<M::Response, M::Error>>, BatchError<M::Error>>
+    where
+        M: RpcMethod + Sync,
+    {
+        let request = methods
+            .iter()
+            .enumerate()
+            .map(|(id, method)| {
+                // The ids are used to match the responses, as the endpoint may reorder them
+                let mut request = methods::to_json(method)?;
+                request["id"] = id.into();
+                Ok(request)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| send_error(JsonRpcTransportSendError::PayloadSerializeError(err)))?;
+        let request = serde_json::Value::Array(request);
+        debug!(
+            target: RPC_CLIENT_TARGET,
+            "Sending batch of {} requests to {}", methods.len(), self.endpoint.url
+        );
+
+        let response = self
+            .transport
+            .send(&self.endpoint, &request)
+            .await
+            .map_err(|err| match transport_error(err) {
+                RpcCallError::JsonRpcError(err) => BatchError::JsonRpcError(err),
+                RpcCallError::TransportError(err) => BatchError::TransportError(err),
+            })?;
+        match response.status {
+            StatusCode::OK => {}
+            StatusCode::TOO_MANY_REQUESTS => {
+                self.throttle(response.retry_after);
+                return Err(JsonRpcError::ServerError(status_error(response.status)).into());
+            }
+            status
+                if status.is_client_error()
+                    && status != StatusCode::UNAUTHORIZED
+                    && status != StatusCode::REQUEST_TIMEOUT =>
+            {
+                return Err(BatchError::Rejected(format!("HTTP status {status}")));
+            }
+            status => return Err(JsonRpcError::ServerError(status_error(status)).into()),
+        }
+
+        let Ok(serde_json::Value::Array(items)) = serde_json::from_slice(&response.body) else {
+            return Err(BatchError::Rejected(
+                "response is not a JSON-RPC batch".to_string(),
+            ));
+        };
+        let mut results: Vec<_> = methods.iter().map(|_| None).collect();
+        for item in items {
+            let id = item["id"].as_u64().and_then(|id| usize::try_from(id).ok());
+            if let Some(result) = id.and_then(|id| results.get_mut(id)) {
+                *result = Some(parse_message::<M>(Ok(item)));
+            }
+        }
+        results.into_iter().collect::<Option<_>>().ok_or_else(|| {
+            BatchError::Rejected("response doesn't contain all the requests".to_string())
+        })
+    }
+
+    #[allow(clippy::result_large_err)]
+    async fn call_with<M>(&self, method: M, coalesce: bool) -> CallResult<M>
+    where
+        M: RpcMethod,
+    {
+        let request = methods::to_json(&method)
+            .map_err(|err| send_error(JsonRpcTransportSendError::PayloadSerializeError(err)))?;
+
+        let Some(observer) = &self.observer else {
+            return self.send::<M>(&request, coalesce).await;
+        };
+
+        let started_at = std::time::Instant::now();
+        let result = self.send::<M>(&request, coalesce).await;
+        observer.on_rpc_call(&RpcCallEvent {
+            endpoint: &self.endpoint.url,
+            method: method.method_name(),
+            latency: started_at.elapsed(),
+            error: result.as_ref().err().map(RpcErrorKind::from),
+        });
+        result
+    }
+
+    #[allow(clippy::result_large_err)]
+    async fn send<M>(&self, request: &serde_json::Value, coalesce: bool) -> CallResult<M>
+    where
+        M: RpcMethod,
+    {
+        debug!(
+            target: RPC_CLIENT_TARGET,
+            "Sending {request} to {}", self.endpoint.url
+        );
+
+        let response = if coalesce {
+            self.in_flight
+                .send(&self.transport, &self.endpoint, request)
+                .await
+        } else {
+            self.transport.send(&self.endpoint, request).await
+        };
+        let response = response.map_err(transport_error)?;
+
+        if response.status == StatusCode::TOO_MANY_REQUESTS {
+            self.throttle(response.retry_after);
+        }
+        if response.status != StatusCode::OK {
+            return Err(JsonRpcError::ServerError(status_error(response.status)).into());
+        }
+
+        let result = parse_response::<M>(&response.body);
+        if let (Ok(_), Some(cache)) = (&result, &self.cache) {
+            cache.insert(&self.endpoint.url, request, &response.body);
+        }
+        Ok(result?)
+    }
+
+    /// Pauses the requests to the endpoint after `429 Too Many Requests`.
+    ///
+    /// The `Retry-After` is capped by the [RPCEndpoint::max_sleep], so the endpoint can't park the requests for hours.
+    fn throttle(&self, retry_after: Option<Duration>) {
+        let max_retry_after = self.endpoint.max_sleep.unwrap_or(MAX_RETRY_AFTER);
+        self.rate_limiter.throttle(
+            &self.endpoint.url,
+            retry_after
+                .unwrap_or(DEFAULT_RETRY_AFTER)
+                .min(max_retry_after),
+        );
+    }
+}
+
+/// near-jsonrpc-client errors can carry only reqwest errors, so the rest are returned as is.
+fn transport_error<E>(err: TransportError) -> RpcCallError<E> {
+    match err {
+        TransportError::Http(err) => {
+            send_error(JsonRpcTransportSendError::PayloadSendError(err)).into()
+        }
+        err => RpcCallError::TransportError(err),
+    }
+}
+
+const fn send_error<E>(err: JsonRpcTransportSendError) -> JsonRpcError<E> {
+    JsonRpcError::TransportError(RpcTransportError::SendError(err))
+}
+
+const fn recv_error<E>(err: JsonRpcTransportRecvError) -> JsonRpcError<E> {
+    JsonRpcError::TransportError(RpcTransportError::RecvError(err))
+}
+
+#[allow(clippy::result_large_err)]
+pub(super) fn parse_response<M: RpcMethod>(body: &[u8]) -> MethodCallResult<M::Response, M::Error> {
+    parse_message::<M>(serde_json::from_slice(body))
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_message<M: RpcMethod>(
+    response_payload: Result<serde_json::Value, serde_json::Error>,
+) -> MethodCallResult<M::Response, M::Error> {
+    let response_message = near_jsonrpc_primitives::message::decoded_to_parsed(
+        response_payload.and_then(serde_json::from_value),
+    )
+    .map_err(|err| recv_error(JsonRpcTransportRecvError::PayloadParseError(err)))?;
+
+    if let near_jsonrpc_primitives::message::Message::Response(response) = response_message {
+        return M::parse_handler_response(response.result?)
+            .map_err(|err| {
+                recv_error(JsonRpcTransportRecvError::ResponseParseError(
+                    JsonRpcTransportHandlerResponseError::ResultParseError(err),
+                ))
+            })?
+            .map_err(|err| JsonRpcError::ServerError(JsonRpcServerError::HandlerError(err)));
+    }
+    Err(recv_error(
+        JsonRpcTransportRecvError::UnexpectedServerResponse(response_message),
+    ))
+}
+
+fn status_error<E>(status: StatusCode) -> JsonRpcServerError<E> {
+    match status {
+        StatusCode::UNAUTHORIZED => {
+            JsonRpcServerError::ResponseStatusError(JsonRpcServerResponseStatusError::Unauthorized)
+        }
+        StatusCode::TOO_MANY_REQUESTS => JsonRpcServerError::ResponseStatusError(
+            JsonRpcServerResponseStatusError::TooManyRequests,
+        ),
+        StatusCode::BAD_REQUEST => {
+            JsonRpcServerError::ResponseStatusError(JsonRpcServerResponseStatusError::BadRequest)
+        }
+        StatusCode::INTERNAL_SERVER_ERROR => JsonRpcServerError::InternalError {
+            info: Some(String::from("Internal server error")),
+        },
+        StatusCode::SERVICE_UNAVAILABLE => JsonRpcServerError::ResponseStatusError(
+            JsonRpcServerResponseStatusError::ServiceUnavailable,
+        ),
+        StatusCode::REQUEST_TIMEOUT => {
+            JsonRpcServerError::ResponseStatusError(JsonRpcServerResponseStatusError::TimeoutError)
+        }
+        unexpected => {
+            JsonRpcServerError::ResponseStatusError(JsonRpcServerResponseStatusError::Unexpected {
+                status: unexpected,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use near_jsonrpc_client::methods::gas_price::RpcGasPriceRequest;
+
+    use crate::{
+        config::{retry, AttemptFailedEvent, RetryResponse, RpcResponse},
+        errors::{QueryError, RetryError},
+        testing::{network, rpc_result, ScriptedTransport},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn requests_go_through_transport() {
+        let (network, transport) = network(ScriptedTransport::responses([
+            RpcResponse {
+                retry_after: Some(Duration::ZERO),
+                ..RpcResponse::status(StatusCode::TOO_MANY_REQUESTS)
+            },
+            rpc_result(serde_json::json!({"gas_price": "100000000"})),
+        ]));
+
+        let gas_price = retry(network, |client| async move {
+            RetryResponse::from(client.call(RpcGasPriceRequest { block_id: None }).await)
+        })
+        .await
+        .unwrap();
+        assert_eq!(gas_price.gas_price, 100_000_000);
+        assert!(transport
+            .requests()
+            .iter()
+            .all(|request| request["method"] == "gas_price"));
+    }
+
+    #[derive(Debug, Default)]
+    struct RecordingObserver {
+        calls: Mutex<Vec<Option<RpcErrorKind>>>,
+        failed_attempts: Mutex<u32>,
+    }
+
+    impl RpcObserver for Arc<RecordingObserver> {
+        fn on_rpc_call(&self, event: &RpcCallEvent<'_>) {
+            assert_eq!(event.method, "gas_price");
+            self.calls.lock().unwrap().push(event.error);
+        }
+
+        fn on_attempt_failed(&self, _event: &AttemptFailedEvent<'_>) {
+            *self.failed_attempts.lock().unwrap() += 1;
+        }
+    }
+
+    #[tokio::test]
+    async fn observer_sees_calls_and_retries() {
+        let observer = Arc::new(RecordingObserver::default());
+        let (network, _) = network(ScriptedTransport::responses([
+            RpcResponse::status(StatusCode::SERVICE_UNAVAILABLE),
+            rpc_result(serde_json::json!({"gas_price": "1"})),
+        ]));
+        let network = network.with_observer(observer.clone());
+
+        retry(network, |client| async move {
+            RetryResponse::from(client.call(RpcGasPriceRequest { block_id: None }).await)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            *observer.calls.lock().unwrap(),
+            vec![Some(RpcErrorKind::ResponseStatus(503)), None]
+        );
+        assert_eq!(*observer.failed_attempts.lock().unwrap(), 1);
+    }
+
+    #[derive(Debug)]
+    struct FailingTransport;
+
+    #[async_trait::async_trait]
+    impl RpcTransport for FailingTransport {
+        async fn send(
+            &self,
+            _endpoint: &RPCEndpoint,
+            _request: &serde_json::Value,
+        ) -> Result<RpcResponse, TransportError> {
+            Err(TransportError::Other("proxy is unreachable".into()))
+        }
+    }
+
+    #[tokio::test]
+    async fn transport_failure_is_reported() {
+        let (network, _) = network(ScriptedTransport::default());
+        let network = network.with_transport(FailingTransport);
+
+        let error = crate::Chain::block_number()
+            .fetch_from(&network)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                error,
+                QueryError::JsonRpcError(RetryError::Transport(TransportError::Other(_)))
+            ),
+            "{error:?}"
+        );
+        assert!(
+            error.to_string().contains("proxy is unreachable"),
+            "{error}"
+        );
+    }
+
+    /// Responds to the batches in the reverse order, and fails the requests for the odd blocks.
+    fn batch_transport(supports_batches: bool) -> ScriptedTransport {
+        ScriptedTransport::new(move |_, request| {
+            if !supports_batches {
+                return RpcResponse::status(StatusCode::BAD_REQUEST);
+            }
+            let responses: Vec<_> = request
+                .as_array()
+                .unwrap()
+                .iter()
+                .rev()
+                .map(|request| {
+                    let block_height = request["params"][0].as_u64().unwrap();
+                    if block_height % 2 == 0 {
+                        serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": {"gas_price": block_height.to_string()}})
+                    } else {
+                        serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "error": {"code": -32601, "message": "Method not found", "data": null}})
+                    }
+                })
+                .collect();
+            RpcResponse::ok(serde_json::to_string(&responses).unwrap())
+        })
+    }
+
+    #[tokio::test]
+    async fn batch_responses_are_matched_by_id() {
+        let requests = [2, 3, 4].map(|block_height| RpcGasPriceRequest {
+            block_id: Some(near_primitives::types::BlockId::Height(block_height)),
+        });
+
+        let client = network(batch_transport(true)).0.rpc_client(0);
+        let results = client.call_batch(&requests).await.unwrap();
+        assert_eq!(results[0].as_ref().unwrap().gas_price, 2);
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap().gas_price, 4);
+
+        let client = network(batch_transport(false)).0.rpc_client(0);
+        assert!(matches!(
+            client.call_batch(&requests).await,
+            Err(BatchError::Rejected(_))
+        ));
+    }
+}
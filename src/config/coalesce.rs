@@ -0,0 +1,226 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use futures::{
+    future::{BoxFuture, Shared},
+    FutureExt,
+};
+use tracing::trace;
+
+use crate::errors::{CassetteError, TransportError};
+
+use super::{RPCEndpoint, RpcResponse, RpcTransport};
+
+const COALESCE_TARGET: &str = "near_api::rpc::coalesce";
+
+type Key = (url::Url, String, String);
+type SharedResponse = Shared<BoxFuture<'static, Result<Arc<RpcResponse>, Arc<TransportError>>>>;
+
+struct InFlight {
+    response: SharedResponse,
+    /// Number of the requests that wait for the response.
+    waiters: usize,
+}
+
+/// Requests that are being sent to the RPC endpoints right now.
+///
+/// Identical requests to the same endpoint that are sent while the first one is in flight
+/// wait for its response instead of sending their own. It's shared between the clones of the [NetworkConfig](super::NetworkConfig).
+///
+/// The request is forgotten once all its waiters are dropped, e.g. by the attempt timeout,
+/// so the retry of the hung request is sent again instead of joining it.
+#[derive(Clone, Default)]
+pub struct InFlightRequests {
+    requests: Arc<Mutex<HashMap<Key, InFlight>>>,
+}
+
+impl std::fmt::Debug for InFlightRequests {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InFlightRequests")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl InFlightRequests {
+    /// Number of the distinct requests in flight.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Sends the request through the transport, or joins the identical request in flight.
+    ///
+    /// The transport error is shared as [TransportError::Other] with the error message,
+    /// as the errors can't be cloned. The cassette misses are kept, so every waiter reports them.
+    pub(crate) async fn send(
+        &self,
+        transport: &Arc<dyn RpcTransport>,
+        endpoint: &RPCEndpoint,
+        request: &serde_json::Value,
+    ) -> Result<RpcResponse, TransportError> {
+        let key = (
+            endpoint.url.clone(),
+            request["method"].as_str().unwrap_or_default().to_string(),
+            request["params"].to_string(),
+        );
+        let waiter = self.join_or_send(key, || {
+            let (transport, endpoint, request) =
+                (transport.clone(), endpoint.clone(), request.clone());
+            async move {
+                transport
+                    .send(&endpoint, &request)
+                    .await
+                    .map(Arc::new)
+                    .map_err(Arc::new)
+            }
+            .boxed()
+            .shared()
+        });
+
+        waiter
+            .response
+            .clone()
+            .await
+            .map(Arc::unwrap_or_clone)
+            .map_err(|err| match err.as_ref() {
+                TransportError::Cassette(CassetteError::UnrecordedRequest { method, params }) => {
+                    TransportError::Cassette(CassetteError::UnrecordedRequest {
+                        method: method.clone(),
+                        params: params.clone(),
+                    })
+                }
+                err => TransportError::Other(err.to_string().into()),
+            })
+    }
+
+    fn join_or_send(&self, key: Key, send: impl FnOnce() -> SharedResponse) -> Waiter<'_> {
+        let mut requests = self.lock();
+        let in_flight = requests.entry(key.clone()).or_insert_with(|| InFlight {
+            response: send(),
+            waiters: 0,
+        });
+        if in_flight.waiters > 0 {
+            trace!(target: COALESCE_TARGET, "Joining <{}> request in flight to {}", key.1, key.0);
+        }
+        in_flight.waiters += 1;
+        let response = in_flight.response.clone();
+        drop(requests);
+        Waiter {
+            requests: self,
+            key,
+            response,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Key, InFlight>> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Request that waits for the response in flight, whether it has sent it or joined.
+struct Waiter<'a> {
+    requests: &'a InFlightRequests,
+    key: Key,
+    response: SharedResponse,
+}
+
+impl Drop for Waiter<'_> {
+    /// Whoever sees the response first removes it, so the next requests are sent again.
+    /// The last waiter removes the response that nobody waits for anymore.
+    fn drop(&mut self) {
+        let mut requests = self.requests.lock();
+        // The check is needed because it could be already replaced by the next request
+        let Some(in_flight) = requests
+            .get_mut(&self.key)
+            .filter(|in_flight| in_flight.response.ptr_eq(&self.response))
+        else {
+            return;
+        };
+        in_flight.waiters -= 1;
+        if in_flight.waiters == 0 || self.response.peek().is_some() {
+            requests.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::testing::{network, ScriptedTransport};
+
+    use super::*;
+
+    fn echo(_endpoint: &RPCEndpoint, request: &serde_json::Value) -> RpcResponse {
+        RpcResponse::ok(request["params"].to_string())
+    }
+
+    #[tokio::test]
+    async fn identical_requests_share_one_call() {
+        let transport =
+            Arc::new(ScriptedTransport::new(echo).with_latency(|_| Duration::from_millis(50)));
+        let dyn_transport: Arc<dyn RpcTransport> = transport.clone();
+        let in_flight = InFlightRequests::default();
+        let endpoint = RPCEndpoint::testnet();
+        let request = |params: u8| serde_json::json!({"method": "query", "params": [params]});
+        let requests = [1, 1, 1, 2].map(request);
+
+        let responses = futures::future::join_all(requests.iter().map(|request| async {
+            in_flight
+                .send(&dyn_transport, &endpoint, request)
+                .await
+                .unwrap()
+                .body
+        }))
+        .await;
+        assert_eq!(responses, [b"[1]", b"[1]", b"[1]", b"[2]"]);
+        assert_eq!(transport.requests().len(), 2);
+        assert!(in_flight.is_empty());
+
+        in_flight
+            .send(&dyn_transport, &endpoint, &request(1))
+            .await
+            .unwrap();
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn timed_out_request_is_sent_again() {
+        // Hangs on the first request, and responds to the next ones
+        let transport = Arc::new(ScriptedTransport::new(echo).with_latency(|index| {
+            if index == 0 {
+                Duration::from_secs(3600)
+            } else {
+                Duration::ZERO
+            }
+        }));
+        let dyn_transport: Arc<dyn RpcTransport> = transport.clone();
+        let in_flight = InFlightRequests::default();
+        let (mut network, _) = network(ScriptedTransport::default());
+        network.rpc_endpoints[0] = network.rpc_endpoints[0]
+            .clone()
+            .with_attempt_timeout(Duration::from_millis(20));
+        let endpoint = network.rpc_endpoints[0].clone();
+        let request = serde_json::json!({"method": "query", "params": [1]});
+
+        let response = crate::config::retry(network, |_| async {
+            match in_flight.send(&dyn_transport, &endpoint, &request).await {
+                Ok(response) => crate::config::RetryResponse::Ok(response),
+                Err(err) => crate::config::RetryResponse::Retry(err),
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(response.body, b"[1]");
+        assert_eq!(transport.requests().len(), 2);
+        assert!(in_flight.is_empty());
+    }
+}
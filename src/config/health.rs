@@ -0,0 +1,314 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tracing::{debug, warn};
+
+use super::{EndpointVerification, RPCEndpoint};
+
+const HEALTH_TARGET: &str = "near_api::rpc::health";
+
+/// Weight of the latest sample in the latency moving average.
+const LATENCY_EWMA_WEIGHT: f64 = 0.2;
+
+/// Circuit breaker settings of the [RPCEndpoint]. The breaker is disabled by default,
+/// see [RPCEndpoint::with_circuit_breaker].
+///
+/// Only the failures of the endpoint itself are counted: transport errors, `5xx`, `408` and `429` responses
+/// and timed out attempts. Errors like the transaction that isn't executed yet don't affect the endpoint health.
+/// Once the endpoint fails `failure_threshold` times in a row, it is skipped by all the requests
+/// that share the same [NetworkConfig](super::NetworkConfig) for the `cooldown` period.
+/// After the cooldown, the endpoint is probed again: a successful request closes the breaker,
+/// while a failed one trips it for another cooldown period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CircuitBreaker {
+    /// Number of consecutive failures that trips the breaker.
+    pub failure_threshold: u32,
+    /// How long the endpoint is skipped after the breaker trips.
+    pub cooldown: Duration,
+}
+
+impl CircuitBreaker {
+    /// 5 consecutive failures, which is the default number of the endpoint retries, trip the breaker for 30 seconds.
+    pub const DEFAULT: Self = Self::new(5, Duration::from_secs(30));
+
+    pub const fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold,
+            cooldown,
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// The endpoint is healthy and receives requests.
+    Closed,
+    /// The endpoint is skipped until the given instant.
+    Open { until: Instant },
+    /// The cooldown has passed and the next request will probe the endpoint.
+    HalfOpen,
+}
+
+/// Health statistics of a single RPC endpoint.
+#[derive(Debug, Clone, Default)]
+pub struct EndpointHealth {
+    pub consecutive_failures: u32,
+    pub total_failures: u64,
+    pub total_successes: u64,
+    pub last_failure: Option<Instant>,
+    /// Exponential moving average of the successful request latency.
+    pub latency: Option<Duration>,
+    /// Result of the chain identity verification, if the endpoint was verified.
+    pub verification: Option<EndpointVerification>,
+    /// The endpoint rejected the JSON-RPC batch request, so the batches are not sent to it anymore.
+    pub batch_rejected: bool,
+    open_until: Option<Instant>,
+}
+
+impl EndpointHealth {
+    pub fn state(&self, now: Instant) -> CircuitState {
+        match self.open_until {
+            Some(until) if until > now => CircuitState::Open { until },
+            Some(_) => CircuitState::HalfOpen,
+            None => CircuitState::Closed,
+        }
+    }
+
+    pub fn is_available(&self, now: Instant) -> bool {
+        !matches!(self.state(now), CircuitState::Open { .. })
+    }
+
+    fn record_success(&mut self, latency: Duration) {
+        self.consecutive_failures = 0;
+        self.total_successes += 1;
+        self.open_until = None;
+        self.latency = Some(self.latency.map_or(latency, |average| {
+            average.mul_f64(1.0 - LATENCY_EWMA_WEIGHT) + latency.mul_f64(LATENCY_EWMA_WEIGHT)
+        }));
+    }
+
+    fn record_failure(&mut self, breaker: Option<&CircuitBreaker>, now: Instant) -> bool {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        self.total_failures += 1;
+        self.last_failure = Some(now);
+
+        let Some(breaker) = breaker else {
+            return false;
+        };
+        if self.consecutive_failures >= breaker.failure_threshold {
+            self.open_until = Some(now + breaker.cooldown);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Health of the RPC endpoints shared between all the clones of the [NetworkConfig](super::NetworkConfig).
+///
+/// The state is keyed by the endpoint URL, so it survives reordering of the endpoints list.
+#[derive(Debug, Clone, Default)]
+pub struct RpcHealth {
+    endpoints: Arc<Mutex<HashMap<url::Url, EndpointHealth>>>,
+}
+
+impl RpcHealth {
+    /// Returns a snapshot of the endpoint health, if the endpoint was used before.
+    pub fn endpoint(&self, url: &url::Url) -> Option<EndpointHealth> {
+        self.lock().get(url).cloned()
+    }
+
+    /// Forgets all the collected statistics and closes all the breakers.
+    pub fn reset(&self) {
+        self.lock().clear();
+    }
+
+    pub(crate) fn record_success(&self, url: &url::Url, latency: Duration) {
+        self.lock()
+            .entry(url.clone())
+            .or_default()
+            .record_success(latency);
+    }
+
+    pub(crate) fn verification(&self, url: &url::Url) -> Option<EndpointVerification> {
+        self.lock()
+            .get(url)
+            .and_then(|health| health.verification.clone())
+    }
+
+    pub(crate) fn record_verification(&self, url: &url::Url, verification: EndpointVerification) {
+        self.lock().entry(url.clone()).or_default().verification = Some(verification);
+    }
+
+    pub(crate) fn batch_rejected(&self, url: &url::Url) -> bool {
+        self.lock()
+            .get(url)
+            .is_some_and(|health| health.batch_rejected)
+    }
+
+    pub(crate) fn record_batch_rejected(&self, url: &url::Url) {
+        self.lock().entry(url.clone()).or_default().batch_rejected = true;
+    }
+
+    /// Records the failure and returns `true` if it tripped the circuit breaker.
+    pub(crate) fn record_failure(&self, url: &url::Url, breaker: Option<&CircuitBreaker>) -> bool {
+        let tripped = self
+            .lock()
+            .entry(url.clone())
+            .or_default()
+            .record_failure(breaker, Instant::now());
+        if let Some(breaker) = breaker.filter(|_| tripped) {
+            warn!(
+                target: HEALTH_TARGET,
+                "Circuit breaker tripped for {url}, skipping it for {:?}", breaker.cooldown
+            );
+        }
+        tripped
+    }
+
+    /// Returns the endpoints (with their index in the config) in the order they should be tried.
+    ///
+    /// Endpoints with the closed or half-open breaker keep the configured order. If every endpoint
+    /// is tripped, all of them are returned, starting from the one that recovers first, as it's
+    /// still better to try than to fail without sending a request.
+    pub(crate) fn order_endpoints<'a>(
+        &self,
+        endpoints: &'a [RPCEndpoint],
+    ) -> Vec<(usize, &'a RPCEndpoint)> {
+        let now = Instant::now();
+        let health = self.lock();
+        let open_until = |endpoint: &RPCEndpoint| match health
+            .get(&endpoint.url)
+            .map(|health| health.state(now))
+        {
+            Some(CircuitState::Open { until }) => Some(until),
+            _ => None,
+        };
+
+        let available: Vec<_> = endpoints
+            .iter()
+            .enumerate()
+            .filter(|(_, endpoint)| open_until(endpoint).is_none())
+            .collect();
+        if !available.is_empty() {
+            debug!(
+                target: HEALTH_TARGET,
+                "{} out of {} endpoints are available",
+                available.len(),
+                endpoints.len()
+            );
+            return available;
+        }
+
+        warn!(
+            target: HEALTH_TARGET,
+            "All RPC endpoints have tripped circuit breakers, trying them anyway"
+        );
+        let mut all: Vec<_> = endpoints.iter().enumerate().collect();
+        all.sort_by_key(|(_, endpoint)| open_until(endpoint));
+        all
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<url::Url, EndpointHealth>> {
+        // The state is a plain statistics, so it's fine to use it even if some thread panicked
+        self.endpoints
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(url: &str) -> RPCEndpoint {
+        RPCEndpoint::new(url.parse().unwrap())
+            .with_circuit_breaker(CircuitBreaker::new(2, Duration::from_secs(60)))
+    }
+
+    #[test]
+    fn breaker_trips_after_threshold() {
+        let health = RpcHealth::default();
+        let endpoints = [endpoint("http://a.near"), endpoint("http://b.near")];
+        let breaker = endpoints[0].circuit_breaker.as_ref();
+
+        assert!(!health.record_failure(&endpoints[0].url, breaker));
+        assert_eq!(health.order_endpoints(&endpoints).len(), 2);
+
+        assert!(health.record_failure(&endpoints[0].url, breaker));
+        let order = health.order_endpoints(&endpoints);
+        assert_eq!(order.len(), 1);
+        assert_eq!(order[0].0, 1);
+    }
+
+    #[test]
+    fn success_resets_failures() {
+        let health = RpcHealth::default();
+        let endpoint = endpoint("http://a.near");
+        let breaker = endpoint.circuit_breaker.as_ref();
+
+        health.record_failure(&endpoint.url, breaker);
+        health.record_success(&endpoint.url, Duration::from_millis(10));
+        assert!(!health.record_failure(&endpoint.url, breaker));
+
+        let stats = health.endpoint(&endpoint.url).unwrap();
+        assert_eq!(stats.consecutive_failures, 1);
+        assert_eq!(stats.total_failures, 2);
+        assert_eq!(stats.total_successes, 1);
+    }
+
+    #[test]
+    fn all_tripped_endpoints_are_still_tried() {
+        let health = RpcHealth::default();
+        let endpoints = [endpoint("http://a.near"), endpoint("http://b.near")];
+        let breaker = endpoints[0].circuit_breaker.as_ref();
+
+        for _ in 0..2 {
+            health.record_failure(&endpoints[1].url, breaker);
+        }
+        for _ in 0..2 {
+            health.record_failure(&endpoints[0].url, breaker);
+        }
+
+        let order: Vec<_> = health
+            .order_endpoints(&endpoints)
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(order, vec![1, 0]);
+    }
+
+    #[test]
+    fn half_open_after_cooldown() {
+        let mut health = EndpointHealth::default();
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(1));
+        let now = Instant::now();
+
+        assert!(health.record_failure(Some(&breaker), now));
+        assert!(!health.is_available(now));
+        assert_eq!(
+            health.state(now + Duration::from_secs(2)),
+            CircuitState::HalfOpen
+        );
+    }
+
+    #[test]
+    fn breaker_is_disabled_by_default() {
+        let health = RpcHealth::default();
+        let endpoint = RPCEndpoint::new("http://a.near".parse().unwrap());
+
+        for _ in 0..10 {
+            assert!(!health.record_failure(&endpoint.url, endpoint.circuit_breaker.as_ref()));
+        }
+        assert_eq!(health.order_endpoints(&[endpoint]).len(), 1);
+    }
+}
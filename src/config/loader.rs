@@ -0,0 +1,430 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use near_primitives::types::AccountId;
+use tracing::{debug, info};
+
+use crate::{errors::NetworkConfigError, types::ApiKey};
+
+use super::{NetworkConfig, RPCEndpoint};
+
+const CONFIG_LOADER_TARGET: &str = "near_api::config::loader";
+
+/// Environment variable that points to the near-cli-rs config file.
+const CLI_CONFIG_ENV: &str = "NEAR_CLI_CONFIG";
+
+/// Network connection section of the near-cli-rs `config.toml`.
+///
+/// Only the fields that are relevant for the library are parsed, the rest are ignored.
+#[derive(Debug, Clone, serde::Deserialize)]
+struct CliNetworkConnection {
+    network_name: String,
+    rpc_url: url::Url,
+    rpc_api_key: Option<ApiKey>,
+    linkdrop_account_id: Option<AccountId>,
+    near_social_db_contract_account_id: Option<AccountId>,
+    faucet_url: Option<url::Url>,
+    meta_transaction_relayer_url: Option<url::Url>,
+    fastnear_url: Option<url::Url>,
+    staking_pools_factory_account_id: Option<AccountId>,
+}
+
+impl From<CliNetworkConnection> for NetworkConfig {
+    fn from(connection: CliNetworkConnection) -> Self {
+        let mut endpoint = RPCEndpoint::new(connection.rpc_url);
+        endpoint.api_key = connection.rpc_api_key;
+
+        Self {
+            linkdrop_account_id: connection.linkdrop_account_id,
+            near_social_db_contract_account_id: connection.near_social_db_contract_account_id,
+            faucet_url: connection.faucet_url,
+            meta_transaction_relayer_url: connection.meta_transaction_relayer_url,
+            fastnear_url: connection.fastnear_url,
+            staking_pools_factory_account_id: connection.staking_pools_factory_account_id,
+            ..Self::new(connection.network_name, vec![endpoint])
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct CliConfig {
+    #[serde(default)]
+    network_connection: BTreeMap<String, CliNetworkConnection>,
+}
+
+/// Named collection of network configs.
+///
+/// The names are the connection names, e.g. `mainnet-fastnear`, while [NetworkConfig::network_name]
+/// is the name of the chain the connection points to, e.g. `mainnet`.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkRegistry {
+    networks: BTreeMap<String, NetworkConfig>,
+}
+
+impl NetworkRegistry {
+    /// Registry with the networks known to the library:
+    /// `mainnet`, `testnet`, `mainnet-fastnear`, `testnet-fastnear` and `localnet`.
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+        registry.insert("mainnet", NetworkConfig::mainnet());
+        registry.insert("testnet", NetworkConfig::testnet());
+        registry.insert(
+            "mainnet-fastnear",
+            NetworkConfig {
+                rpc_endpoints: vec![RPCEndpoint::mainnet_fastnear()],
+                ..NetworkConfig::mainnet()
+            },
+        );
+        registry.insert(
+            "testnet-fastnear",
+            NetworkConfig {
+                rpc_endpoints: vec![RPCEndpoint::testnet_fastnear()],
+                ..NetworkConfig::testnet()
+            },
+        );
+        registry.insert("localnet", NetworkConfig::localnet());
+        registry
+    }
+
+    /// Builds the registry from the built-in networks, near-cli-rs config file and environment.
+    ///
+    /// The sources are applied in the following order, so the later ones take precedence:
+    /// 1. [NetworkRegistry::builtin] networks.
+    /// 2. Connections from the near-cli-rs config file located at `NEAR_CLI_CONFIG`,
+    ///    which must exist if the variable is set, or at the [NetworkRegistry::default_cli_config_path], if the file exists.
+    /// 3. Network defined by the environment variables, if `NEAR_RPC_URL` is set.
+    ///    It is registered under the `NEAR_NETWORK_NAME` name (`custom` by default).
+    ///    If the network with this name is already registered, only the provided variables override it.
+    ///
+    /// Supported environment variables:
+    /// `NEAR_NETWORK_NAME`, `NEAR_RPC_URL` (comma-separated list of endpoints), `NEAR_RPC_API_KEY`,
+    /// `NEAR_LINKDROP_ACCOUNT_ID`, `NEAR_SOCIAL_DB_CONTRACT_ACCOUNT_ID`, `NEAR_FAUCET_URL`,
+    /// `NEAR_META_TRANSACTION_RELAYER_URL`, `NEAR_FASTNEAR_URL`, `NEAR_STAKING_POOLS_FACTORY_ACCOUNT_ID`.
+    pub fn load_default() -> Result<Self, NetworkConfigError> {
+        let mut registry = Self::builtin();
+
+        registry.extend_from_cli_config(std::env::var_os(CLI_CONFIG_ENV).map(PathBuf::from))?;
+        registry.extend_from_env(|name| std::env::var(name).ok())?;
+        Ok(registry)
+    }
+
+    /// Location of the near-cli-rs config file, e.g. `~/.config/near-cli/config.toml` on Linux.
+    pub fn default_cli_config_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("near-cli").join("config.toml"))
+    }
+
+    /// Loads the `network_connection` sections of the near-cli-rs config file.
+    pub fn from_cli_config_file(path: impl AsRef<Path>) -> Result<Self, NetworkConfigError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_cli_config_str(&content)
+    }
+
+    /// Parses the `network_connection` sections of the near-cli-rs config.
+    pub fn from_cli_config_str(content: &str) -> Result<Self, NetworkConfigError> {
+        let config: CliConfig = toml::from_str(content)?;
+        let networks = config
+            .network_connection
+            .into_iter()
+            .map(|(name, connection)| (name, connection.into()))
+            .collect::<BTreeMap<_, _>>();
+        info!(
+            target: CONFIG_LOADER_TARGET,
+            "Loaded {} network connections from near-cli config",
+            networks.len()
+        );
+        Ok(Self { networks })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&NetworkConfig> {
+        self.networks.get(name)
+    }
+
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        network: NetworkConfig,
+    ) -> Option<NetworkConfig> {
+        self.networks.insert(name.into(), network)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<NetworkConfig> {
+        self.networks.remove(name)
+    }
+
+    /// Adds the networks from another registry, overriding the ones with the same name.
+    pub fn extend(&mut self, other: Self) {
+        self.networks.extend(other.networks);
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.networks.keys().map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &NetworkConfig)> {
+        self.networks
+            .iter()
+            .map(|(name, network)| (name.as_str(), network))
+    }
+
+    /// Loads the networks from the config file at the given path, or at the default location if it exists.
+    fn extend_from_cli_config(
+        &mut self,
+        config_path: Option<PathBuf>,
+    ) -> Result<(), NetworkConfigError> {
+        let path = match config_path {
+            Some(path) if !path.exists() => {
+                return Err(NetworkConfigError::CliConfigNotFound(path))
+            }
+            Some(path) => path,
+            None => match Self::default_cli_config_path().filter(|path| path.exists()) {
+                Some(path) => path,
+                None => return Ok(()),
+            },
+        };
+        debug!(target: CONFIG_LOADER_TARGET, "Loading networks from {}", path.display());
+        self.extend(Self::from_cli_config_file(path)?);
+        Ok(())
+    }
+
+    fn extend_from_env(
+        &mut self,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<(), NetworkConfigError> {
+        if lookup("NEAR_RPC_URL").is_none() {
+            return Ok(());
+        }
+
+        let name = env_network_name(&lookup);
+        let network = match self.networks.remove(&name) {
+            Some(mut network) => {
+                apply_env_overrides(&mut network, &lookup)?;
+                network
+            }
+            None => network_from_env(&lookup)?,
+        };
+        debug!(target: CONFIG_LOADER_TARGET, "Registering network <{name}> from environment");
+        self.networks.insert(name, network);
+        Ok(())
+    }
+}
+
+pub(super) fn network_from_env(
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<NetworkConfig, NetworkConfigError> {
+    let mut network = NetworkConfig::new(env_network_name(&lookup), vec![]);
+    apply_env_overrides(&mut network, &lookup)?;
+    if network.rpc_endpoints.is_empty() {
+        return Err(NetworkConfigError::RpcUrlIsNotDefined);
+    }
+    Ok(network)
+}
+
+fn env_network_name(lookup: impl Fn(&str) -> Option<String>) -> String {
+    lookup("NEAR_NETWORK_NAME").unwrap_or_else(|| "custom".to_string())
+}
+
+fn apply_env_overrides(
+    network: &mut NetworkConfig,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<(), NetworkConfigError> {
+    if let Some(urls) = lookup("NEAR_RPC_URL") {
+        network.rpc_endpoints = urls
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(|url| parse_env_value("NEAR_RPC_URL", url).map(RPCEndpoint::new))
+            .collect::<Result<_, _>>()?;
+    }
+    if let Some(api_key) = env_value::<ApiKey>(&lookup, "NEAR_RPC_API_KEY")? {
+        for endpoint in &mut network.rpc_endpoints {
+            endpoint.api_key = Some(api_key.clone());
+        }
+    }
+
+    if let Some(value) = env_value(&lookup, "NEAR_LINKDROP_ACCOUNT_ID")? {
+        network.linkdrop_account_id = Some(value);
+    }
+    if let Some(value) = env_value(&lookup, "NEAR_SOCIAL_DB_CONTRACT_ACCOUNT_ID")? {
+        network.near_social_db_contract_account_id = Some(value);
+    }
+    if let Some(value) = env_value(&lookup, "NEAR_FAUCET_URL")? {
+        network.faucet_url = Some(value);
+    }
+    if let Some(value) = env_value(&lookup, "NEAR_META_TRANSACTION_RELAYER_URL")? {
+        network.meta_transaction_relayer_url = Some(value);
+    }
+    if let Some(value) = env_value(&lookup, "NEAR_FASTNEAR_URL")? {
+        network.fastnear_url = Some(value);
+    }
+    if let Some(value) = env_value(&lookup, "NEAR_STAKING_POOLS_FACTORY_ACCOUNT_ID")? {
+        network.staking_pools_factory_account_id = Some(value);
+    }
+    Ok(())
+}
+
+fn env_value<T>(
+    lookup: impl Fn(&str) -> Option<String>,
+    name: &'static str,
+) -> Result<Option<T>, NetworkConfigError>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    lookup(name)
+        .map(|value| parse_env_value(name, &value))
+        .transpose()
+}
+
+fn parse_env_value<T>(name: &'static str, value: &str) -> Result<T, NetworkConfigError>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|err: T::Err| NetworkConfigError::InvalidEnvVariable {
+            name,
+            reason: err.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    const CLI_CONFIG: &str = r#"
+version = "2"
+credentials_home_dir = "/home/user/.near-credentials"
+
+[network_connection.mainnet-fastnear]
+network_name = "mainnet"
+rpc_url = "https://rpc.mainnet.fastnear.com/"
+wallet_url = "https://app.mynearwallet.com/"
+explorer_transaction_url = "https://explorer.near.org/transactions/"
+linkdrop_account_id = "near"
+near_social_db_contract_account_id = "social.near"
+staking_pools_factory_account_id = "poolv1.near"
+
+[network_connection.testnet]
+network_name = "testnet"
+rpc_url = "https://archival-rpc.testnet.near.org/"
+rpc_api_key = "secret"
+wallet_url = "https://testnet.mynearwallet.com/"
+explorer_transaction_url = "https://explorer.testnet.near.org/transactions/"
+faucet_url = "https://helper.nearprotocol.com/account"
+meta_transaction_relayer_url = "http://localhost:3030/relay"
+"#;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn parse_cli_config() {
+        let registry = NetworkRegistry::from_cli_config_str(CLI_CONFIG).unwrap();
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            vec!["mainnet-fastnear", "testnet"]
+        );
+
+        let mainnet = registry.get("mainnet-fastnear").unwrap();
+        assert_eq!(mainnet.network_name, "mainnet");
+        assert_eq!(
+            mainnet.rpc_endpoints[0].url.as_str(),
+            "https://rpc.mainnet.fastnear.com/"
+        );
+        assert!(mainnet.rpc_endpoints[0].api_key.is_none());
+        assert_eq!(
+            mainnet.staking_pools_factory_account_id,
+            Some("poolv1.near".parse().unwrap())
+        );
+        assert!(mainnet.faucet_url.is_none());
+
+        let testnet = registry.get("testnet").unwrap();
+        assert_eq!(
+            testnet.rpc_endpoints[0]
+                .api_key
+                .as_ref()
+                .map(ToString::to_string),
+            Some("secret".to_string())
+        );
+        assert!(testnet.meta_transaction_relayer_url.is_some());
+    }
+
+    #[test]
+    fn network_from_env_variables() {
+        let network = network_from_env(lookup(&[
+            ("NEAR_NETWORK_NAME", "localnet"),
+            (
+                "NEAR_RPC_URL",
+                "http://127.0.0.1:3030, http://127.0.0.1:3031",
+            ),
+            ("NEAR_RPC_API_KEY", "key"),
+            ("NEAR_LINKDROP_ACCOUNT_ID", "test.near"),
+        ]))
+        .unwrap();
+
+        assert_eq!(network.network_name, "localnet");
+        assert_eq!(network.rpc_endpoints.len(), 2);
+        assert!(network
+            .rpc_endpoints
+            .iter()
+            .all(|endpoint| endpoint.api_key.is_some()));
+        assert_eq!(
+            network.linkdrop_account_id,
+            Some("test.near".parse().unwrap())
+        );
+
+        assert!(matches!(
+            network_from_env(lookup(&[])),
+            Err(NetworkConfigError::RpcUrlIsNotDefined)
+        ));
+        assert!(matches!(
+            network_from_env(lookup(&[("NEAR_RPC_URL", "not a url")])),
+            Err(NetworkConfigError::InvalidEnvVariable {
+                name: "NEAR_RPC_URL",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn explicit_cli_config_must_exist() {
+        let path = std::env::temp_dir().join("near-api-missing-cli-config.toml");
+        let error = NetworkRegistry::builtin()
+            .extend_from_cli_config(Some(path.clone()))
+            .unwrap_err();
+        assert!(
+            matches!(&error, NetworkConfigError::CliConfigNotFound(missing) if *missing == path),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn env_overrides_registered_network() {
+        let mut registry = NetworkRegistry::builtin();
+        registry
+            .extend_from_env(lookup(&[
+                ("NEAR_NETWORK_NAME", "mainnet"),
+                ("NEAR_RPC_URL", "https://rpc.example.com"),
+            ]))
+            .unwrap();
+
+        let mainnet = registry.get("mainnet").unwrap();
+        assert_eq!(mainnet.rpc_endpoints.len(), 1);
+        assert_eq!(
+            mainnet.rpc_endpoints[0].url.as_str(),
+            "https://rpc.example.com/"
+        );
+        assert_eq!(mainnet.linkdrop_account_id, Some("near".parse().unwrap()));
+    }
+}
@@ -0,0 +1,1065 @@
+use std::sync::Arc;
+
+use futures::{future::Either, stream::FuturesUnordered, StreamExt};
+use near_jsonrpc_client::methods::RpcMethod;
+use tracing::{debug, warn};
+
+use crate::errors::{
+    AttemptError, CassetteError, FailedAttempt, NetworkConfigError, RetryError, RetryReport,
+    TransportError,
+};
+
+pub use self::{
+    cache::ResponseCache,
+    cassette::{Cassette, Interaction, RecordingTransport, ReplayTransport},
+    client::RpcClient,
+    coalesce::InFlightRequests,
+    health::{CircuitBreaker, CircuitState, EndpointHealth, RpcHealth},
+    loader::NetworkRegistry,
+    observer::{
+        AttemptFailedEvent, OperationEvent, OperationKind, RpcCallEvent, RpcErrorKind, RpcObserver,
+    },
+    rate_limit::{RateLimit, RateLimiter},
+    transport::{shared_client, HttpTransport, RpcResponse, RpcTransport},
+    verification::EndpointVerification,
+};
+
+#[cfg(feature = "metrics")]
+pub use self::observer::MetricsObserver;
+
+mod cache;
+mod cassette;
+mod client;
+mod coalesce;
+mod health;
+mod loader;
+mod observer;
+mod rate_limit;
+mod transport;
+mod verification;
+
+const RETRY_TARGET: &str = "near_api::retry";
+const CONFIG_TARGET: &str = "near_api::config";
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// Using this struct to configure RPC endpoints.
+/// This is primary way to configure retry logic.
+pub struct RPCEndpoint {
+    pub url: url::Url,
+    pub api_key: Option<crate::types::ApiKey>,
+    /// Number of consecutive failures to move on to the next endpoint.
+    pub retries: u8,
+    pub exponential_backoff: bool,
+    pub factor: u8,
+    pub initial_sleep: std::time::Duration,
+    /// Upper bound of the sleep between the retries and of the `Retry-After` the endpoint asks for.
+    #[serde(default)]
+    pub max_sleep: Option<std::time::Duration>,
+    #[serde(default)]
+    pub jitter: Jitter,
+    /// Timeout of the single request attempt. Timed out attempts are retried as the failed ones.
+    #[serde(default)]
+    pub attempt_timeout: Option<std::time::Duration>,
+    /// Circuit breaker that temporarily excludes the failing endpoint from the rotation. Disabled by default.
+    #[serde(default)]
+    pub circuit_breaker: Option<CircuitBreaker>,
+    /// Client-side request budget. Requests over the budget wait locally instead of being sent.
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl RPCEndpoint {
+    pub const fn new(url: url::Url) -> Self {
+        Self {
+            url,
+            api_key: None,
+            retries: 5,
+            exponential_backoff: true,
+            factor: 2,
+            // 10ms, 20ms, 40ms, 80ms, 160ms
+            initial_sleep: std::time::Duration::from_millis(10),
+            max_sleep: None,
+            jitter: Jitter::None,
+            attempt_timeout: None,
+            circuit_breaker: None,
+            rate_limit: None,
+        }
+    }
+
+    pub fn mainnet() -> Self {
+        Self::new("https://archival-rpc.mainnet.near.org".parse().unwrap())
+    }
+
+    pub fn testnet() -> Self {
+        Self::new("https://archival-rpc.testnet.near.org".parse().unwrap())
+    }
+
+    pub fn mainnet_fastnear() -> Self {
+        Self::new("https://rpc.mainnet.fastnear.com".parse().unwrap())
+    }
+
+    pub fn testnet_fastnear() -> Self {
+        Self::new("https://rpc.testnet.fastnear.com".parse().unwrap())
+    }
+
+    /// Set API key for the endpoint.
+    pub fn with_api_key(mut self, api_key: crate::types::ApiKey) -> Self {
+        self.api_key = Some(api_key);
+        self
+    }
+
+    /// Set number of retries for the endpoint before moving on to the next one.
+    pub const fn with_retries(mut self, retries: u8) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Should we use exponential backoff for the endpoint. Default is true.
+    pub const fn with_exponential_backoff(mut self, exponential_backoff: bool, factor: u8) -> Self {
+        self.exponential_backoff = exponential_backoff;
+        self.factor = factor;
+        self
+    }
+
+    /// Set initial sleep duration for the endpoint. Default is 10ms.
+    pub const fn with_initial_sleep(mut self, initial_sleep: std::time::Duration) -> Self {
+        self.initial_sleep = initial_sleep;
+        self
+    }
+
+    /// Set circuit breaker for the endpoint, e.g. [CircuitBreaker::DEFAULT]. Default is no circuit breaker.
+    pub const fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Set client-side rate limit for the endpoint. Default is no limit.
+    pub const fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Set the upper bound of the sleep between the retries. Default is no limit.
+    pub const fn with_max_sleep(mut self, max_sleep: std::time::Duration) -> Self {
+        self.max_sleep = Some(max_sleep);
+        self
+    }
+
+    /// Set the jitter applied to the sleep between the retries. Default is no jitter.
+    pub const fn with_jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the timeout of the single request attempt. Default is no timeout.
+    pub const fn with_attempt_timeout(mut self, attempt_timeout: std::time::Duration) -> Self {
+        self.attempt_timeout = Some(attempt_timeout);
+        self
+    }
+
+    /// Sleep duration before the given retry without the jitter, capped by the [RPCEndpoint::max_sleep].
+    pub fn get_sleep_duration(&self, retry: usize) -> std::time::Duration {
+        let sleep = if self.exponential_backoff {
+            u32::try_from(retry)
+                .ok()
+                .and_then(|retry| (self.factor as u32).checked_pow(retry))
+                .and_then(|multiplier| self.initial_sleep.checked_mul(multiplier))
+                .unwrap_or(std::time::Duration::MAX)
+        } else {
+            self.initial_sleep
+        };
+        self.cap_sleep(sleep)
+    }
+
+    /// Sleep duration before the given retry with the jitter applied.
+    ///
+    /// `previous` is the previous sleep duration, used by the [Jitter::Decorrelated].
+    pub fn next_sleep_duration(
+        &self,
+        retry: usize,
+        previous: Option<std::time::Duration>,
+    ) -> std::time::Duration {
+        use rand::Rng;
+
+        match self.jitter {
+            Jitter::None => self.get_sleep_duration(retry),
+            Jitter::Full => rand::thread_rng()
+                .gen_range(std::time::Duration::ZERO..=self.get_sleep_duration(retry)),
+            Jitter::Decorrelated => {
+                let upper = previous
+                    .unwrap_or(self.initial_sleep)
+                    .saturating_mul(3)
+                    .max(self.initial_sleep);
+                self.cap_sleep(rand::thread_rng().gen_range(self.initial_sleep..=upper))
+            }
+        }
+    }
+
+    fn cap_sleep(&self, sleep: std::time::Duration) -> std::time::Duration {
+        self.max_sleep
+            .map_or(sleep, |max_sleep| sleep.min(max_sleep))
+    }
+}
+
+/// Randomization of the sleep between the retries, so the clients that failed at the same time
+/// don't retry at the same time.
+///
+/// See <https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/>.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Jitter {
+    /// Sleep exactly the backoff duration.
+    #[default]
+    None,
+    /// Sleep a random duration between zero and the backoff duration.
+    Full,
+    /// Sleep a random duration between the initial sleep and three times the previous sleep.
+    /// The exponential backoff settings are ignored.
+    Decorrelated,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct NetworkConfig {
+    pub network_name: String,
+    pub rpc_endpoints: Vec<RPCEndpoint>,
+    // https://github.com/near/near-cli-rs/issues/116
+    pub linkdrop_account_id: Option<near_primitives::types::AccountId>,
+    // https://docs.near.org/social/contract
+    pub near_social_db_contract_account_id: Option<near_primitives::types::AccountId>,
+    pub faucet_url: Option<url::Url>,
+    pub meta_transaction_relayer_url: Option<url::Url>,
+    pub fastnear_url: Option<url::Url>,
+    pub staking_pools_factory_account_id: Option<near_primitives::types::AccountId>,
+    /// Health of the RPC endpoints. It's shared between the clones of the config,
+    /// so all the requests made with the same config skip the endpoints that are known to be down.
+    #[serde(skip)]
+    pub health: RpcHealth,
+    /// Enables hedged view requests: if the endpoint doesn't respond within this delay,
+    /// the same request is sent to the next endpoint, and the first response wins.
+    #[serde(default)]
+    pub hedge_delay: Option<std::time::Duration>,
+    /// Request budgets of the RPC endpoints. It's shared between the clones of the config,
+    /// so concurrent requests to the same endpoint are queued together.
+    #[serde(skip)]
+    pub rate_limiter: RateLimiter,
+    /// Deadline of the whole request, including all the retries across all the endpoints
+    /// and the waits for the [RPCEndpoint::rate_limit] or `Retry-After`.
+    #[serde(default)]
+    pub deadline: Option<std::time::Duration>,
+    /// Transport used to send the requests to the RPC endpoints. Default is [HttpTransport].
+    #[serde(skip, default = "default_transport")]
+    pub transport: Arc<dyn RpcTransport>,
+    /// HTTP client used for the relayer and faucet requests. It's shared between the clones of the config
+    /// and with the default transport, so the connections are kept alive and reused.
+    #[serde(skip, default = "default_http_client")]
+    pub http_client: reqwest::Client,
+    /// Verifies the chain identity of each endpoint with the `status` request before sending
+    /// the first request to it. Mismatching endpoints are excluded from the rotation,
+    /// while the failed `status` request is retried as any other failed attempt.
+    ///
+    /// Without the [NetworkConfig::expected_genesis_hash], the [NetworkConfig::network_name] must equal
+    /// the `chain_id` of the node, which is not the case for the config created from the sandbox worker.
+    ///
+    /// See [NetworkConfig::verify_endpoints].
+    #[serde(default)]
+    pub verify_chain_identity: bool,
+    /// Genesis hash the endpoints must have. If it is not set,
+    /// the `chain_id` of the endpoints is compared with the [NetworkConfig::network_name].
+    #[serde(default)]
+    pub expected_genesis_hash: Option<near_primitives::hash::CryptoHash>,
+    /// Observer of the requests made with the config, e.g. to feed metrics.
+    #[serde(skip)]
+    pub observer: Option<Arc<dyn RpcObserver>>,
+    /// Cache of the view responses. Disabled by default.
+    ///
+    /// See [NetworkConfig::with_response_cache].
+    #[serde(skip)]
+    pub response_cache: Option<ResponseCache>,
+    /// View requests in flight. Identical concurrent queries share one network call.
+    #[serde(skip)]
+    pub in_flight: InFlightRequests,
+    /// Sends the multi-queries as one JSON-RPC batch request. If the endpoint rejects the batch,
+    /// the queries are sent as parallel requests, and the endpoint is not sent batches anymore.
+    /// A failed batch isn't retried, the queries are sent as parallel requests with their own retries.
+    #[serde(default)]
+    pub batch_requests: bool,
+}
+
+fn default_http_client() -> reqwest::Client {
+    shared_client().clone()
+}
+
+fn default_transport() -> Arc<dyn RpcTransport> {
+    Arc::new(HttpTransport::default())
+}
+
+impl NetworkConfig {
+    /// Creates a config for the given network with no optional services defined.
+    pub fn new(network_name: impl Into<String>, rpc_endpoints: Vec<RPCEndpoint>) -> Self {
+        Self {
+            network_name: network_name.into(),
+            rpc_endpoints,
+            linkdrop_account_id: None,
+            near_social_db_contract_account_id: None,
+            faucet_url: None,
+            meta_transaction_relayer_url: None,
+            fastnear_url: None,
+            staking_pools_factory_account_id: None,
+            health: Default::default(),
+            hedge_delay: None,
+            rate_limiter: Default::default(),
+            deadline: None,
+            transport: default_transport(),
+            http_client: default_http_client(),
+            verify_chain_identity: false,
+            expected_genesis_hash: None,
+            observer: None,
+            response_cache: None,
+            in_flight: Default::default(),
+            batch_requests: false,
+        }
+    }
+
+    pub fn mainnet() -> Self {
+        Self {
+            linkdrop_account_id: Some("near".parse().unwrap()),
+            near_social_db_contract_account_id: Some("social.near".parse().unwrap()),
+            fastnear_url: Some("https://api.fastnear.com/".parse().unwrap()),
+            staking_pools_factory_account_id: Some("pool.near".parse().unwrap()),
+            ..Self::new("mainnet", vec![RPCEndpoint::mainnet()])
+        }
+    }
+
+    pub fn testnet() -> Self {
+        Self {
+            linkdrop_account_id: Some("testnet".parse().unwrap()),
+            near_social_db_contract_account_id: Some("v1.social08.testnet".parse().unwrap()),
+            faucet_url: Some("https://helper.nearprotocol.com/account".parse().unwrap()),
+            meta_transaction_relayer_url: Some("http://localhost:3030/relay".parse().unwrap()),
+            staking_pools_factory_account_id: Some("pool.f863973.m0".parse().unwrap()),
+            ..Self::new("testnet", vec![RPCEndpoint::testnet()])
+        }
+    }
+
+    /// Local node, e.g. started with `neard run` or near-sandbox.
+    pub fn localnet() -> Self {
+        Self::new(
+            "localnet",
+            vec![RPCEndpoint::new("http://127.0.0.1:3030".parse().unwrap())],
+        )
+    }
+
+    /// Looks up the network by name in the [NetworkRegistry::load_default] registry.
+    ///
+    /// Built-in names are `mainnet`, `testnet`, `mainnet-fastnear`, `testnet-fastnear` and `localnet`,
+    /// and the ones defined in the near-cli-rs config file.
+    pub fn by_name(name: &str) -> Result<Self, NetworkConfigError> {
+        NetworkRegistry::load_default()?
+            .get(name)
+            .cloned()
+            .ok_or_else(|| NetworkConfigError::UnknownNetwork(name.to_string()))
+    }
+
+    /// Builds the config from the `NEAR_*` environment variables.
+    ///
+    /// See [NetworkRegistry::load_default] for the list of supported variables.
+    pub fn from_env() -> Result<Self, NetworkConfigError> {
+        loader::network_from_env(|name| std::env::var(name).ok())
+    }
+
+    /// Replaces the transport used to send the requests, e.g. to use a custom [reqwest::Client]
+    /// with [HttpTransport::new] or to serve the requests from memory in tests.
+    pub fn with_transport(mut self, transport: impl RpcTransport + 'static) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// Records all the requests made through the current transport into the cassette file.
+    ///
+    /// The cassette is written when the config and all its clones are dropped.
+    /// Use [RecordingTransport] with [NetworkConfig::with_transport] to save it explicitly.
+    /// The cassette can be replayed later with [NetworkConfig::with_replay].
+    pub fn with_recording(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.transport = Arc::new(RecordingTransport::new(self.transport, path));
+        self
+    }
+
+    /// Serves all the requests from the cassette file recorded with [NetworkConfig::with_recording],
+    /// without touching the network. Requests that are not recorded fail.
+    pub fn with_replay(self, path: impl AsRef<std::path::Path>) -> Result<Self, CassetteError> {
+        Ok(self.with_transport(ReplayTransport::from_file(path)?))
+    }
+
+    /// Uses the custom HTTP client, e.g. with a proxy, client certificate or timeouts,
+    /// for the RPC, relayer and faucet requests.
+    ///
+    /// The client is passed to the transport with [RpcTransport::with_http_client], so the [HttpTransport]
+    /// and the recording keep their settings. A transport that doesn't send the requests over HTTP,
+    /// e.g. the replay, is kept as is, and the client is used only for the relayer and faucet requests.
+    pub fn with_http_client(mut self, http_client: reqwest::Client) -> Self {
+        match self.transport.with_http_client(http_client.clone()) {
+            Some(transport) => self.transport = transport,
+            None => warn!(
+                target: CONFIG_TARGET,
+                "Transport {:?} doesn't use the HTTP client, so it's kept for the RPC requests", self.transport
+            ),
+        }
+        self.http_client = http_client;
+        self
+    }
+
+    /// Verifies the chain identity of all the endpoints by calling the `status` RPC method.
+    ///
+    /// The endpoint is verified if its genesis hash matches the [NetworkConfig::expected_genesis_hash],
+    /// or if its `chain_id` matches the [NetworkConfig::network_name] when the genesis hash isn't set.
+    /// So the genesis hash must be set if the network name differs from the `chain_id` of the nodes,
+    /// e.g. for the config created from the sandbox worker, which is named after the worker.
+    /// Mismatching endpoints are remembered and excluded from the rotation for all the clones of the config,
+    /// even if [NetworkConfig::verify_chain_identity] is disabled.
+    pub async fn verify_endpoints(&self) -> Vec<(url::Url, EndpointVerification)> {
+        futures::future::join_all((0..self.rpc_endpoints.len()).map(|index| async move {
+            (
+                self.rpc_endpoints[index].url.clone(),
+                verification::verify_endpoint(self, &self.rpc_client(index))
+                    .await
+                    .unwrap_or_else(|err| EndpointVerification::Unavailable(err.to_string())),
+            )
+        }))
+        .await
+    }
+
+    /// Sets the observer of the requests made with the config.
+    pub fn with_observer(mut self, observer: impl RpcObserver + 'static) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Caches the responses of the `query`, `block` and `validators` requests,
+    /// so the repeated queries at the same block don't cost a round trip.
+    pub fn with_response_cache(mut self, cache: ResponseCache) -> Self {
+        self.response_cache = Some(cache);
+        self
+    }
+
+    pub(crate) fn rpc_client(&self, index: usize) -> RpcClient {
+        RpcClient::new(
+            self.rpc_endpoints[index].clone(),
+            self.transport.clone(),
+            self.rate_limiter.clone(),
+            self.observer.clone(),
+            self.response_cache.clone(),
+            self.in_flight.clone(),
+        )
+    }
+
+    /// Returns the cached response to the request from the first endpoint that has it.
+    ///
+    /// It's looked up before the retries, so the cache hits don't wait for the rate limiter or the endpoint verification.
+    pub(crate) fn cached_response<M: RpcMethod>(&self, method: &M) -> Option<M::Response> {
+        let cache = self.response_cache.as_ref()?;
+        let request = near_jsonrpc_client::methods::to_json(method).ok()?;
+        self.rpc_endpoints.iter().find_map(|endpoint| {
+            let body = cache.get(&endpoint.url, &request)?;
+            debug!(target: RETRY_TARGET, "Serving {request} from the cache of {}", endpoint.url);
+            client::parse_response::<M>(&body).ok()
+        })
+    }
+
+    pub(crate) fn observe(&self, notify: impl FnOnce(&dyn RpcObserver)) {
+        if let Some(observer) = &self.observer {
+            notify(observer.as_ref());
+        }
+    }
+
+    pub(crate) fn observe_operation(
+        &self,
+        kind: OperationKind,
+        started_at: std::time::Instant,
+        success: bool,
+    ) {
+        self.observe(|observer| {
+            observer.on_operation(&OperationEvent {
+                kind,
+                latency: started_at.elapsed(),
+                success,
+            })
+        });
+    }
+}
+
+/// The config is named after the worker, which differs from the `chain_id` of the sandbox node,
+/// so set the [NetworkConfig::expected_genesis_hash] before verifying the endpoint identity.
+#[cfg(feature = "workspaces")]
+impl<T: near_workspaces::Network> From<near_workspaces::Worker<T>> for NetworkConfig {
+    fn from(network: near_workspaces::Worker<T>) -> Self {
+        use near_workspaces::network::NetworkInfo;
+
+        let info = network.info();
+        Self::new(
+            info.name.clone(),
+            vec![RPCEndpoint::new(info.rpc_url.clone())],
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum RetryResponse<R, E> {
+    Ok(R),
+    /// The endpoint failed, e.g. with a transport error or `5xx` response.
+    /// The failure counts against the [RPCEndpoint::circuit_breaker].
+    Retry(E),
+    /// The endpoint is fine, but the result isn't ready yet, e.g. the transaction isn't executed.
+    /// Retried the same way, but doesn't count against the [RPCEndpoint::circuit_breaker].
+    Pending(E),
+    Critical(E),
+    /// The custom [RpcTransport] failed, see [RetryError::Transport].
+    Transport(TransportError),
+}
+
+impl<R, E> From<Result<R, E>> for RetryResponse<R, E> {
+    fn from(value: Result<R, E>) -> Self {
+        match value {
+            Ok(value) => Self::Ok(value),
+            Err(value) => Self::Retry(value),
+        }
+    }
+}
+
+pub async fn retry<R, E, T, F>(network: NetworkConfig, mut task: F) -> Result<R, RetryError<E>>
+where
+    F: FnMut(RpcClient) -> T + Send,
+    T: core::future::Future<Output = RetryResponse<R, E>> + Send,
+    T::Output: Send,
+    E: Send,
+{
+    if network.rpc_endpoints.is_empty() {
+        return Err(RetryError::NoRpcEndpoints);
+    }
+
+    let deadline = Deadline::new(network.deadline);
+    let mut attempts = Vec::new();
+    let endpoints = network.health.order_endpoints(&network.rpc_endpoints);
+    let count = endpoints.len();
+    for (position, (index, endpoint)) in endpoints.into_iter().enumerate() {
+        let has_fallback = position + 1 < count;
+        match retry_endpoint(&network, index, endpoint, has_fallback, deadline, &mut task).await {
+            EndpointOutcome::Ok(result) => return Ok(result),
+            EndpointOutcome::Critical(error) => return Err(RetryError::Critical(error)),
+            EndpointOutcome::Transport(error) => return Err(RetryError::Transport(error)),
+            EndpointOutcome::Exhausted(failed) => attempts.extend(failed),
+            EndpointOutcome::DeadlineExceeded(failed) => {
+                attempts.extend(failed);
+                return Err(deadline.exceeded(attempts));
+            }
+        }
+    }
+    Err(RetryError::RetriesExhausted(RetryReport { attempts }))
+}
+
+/// Same as [retry], but hedges the request if [NetworkConfig::hedge_delay] is set.
+///
+/// The request is sent to the first endpoint, and if it doesn't succeed within the hedge delay,
+/// the same request is sent to the next endpoint while the first one is still in flight.
+/// The first successful response wins and the rest of the requests are cancelled.
+/// If an endpoint exhausts its retries, the next one is started immediately.
+///
+/// Should be used only for idempotent requests, e.g. view calls.
+pub async fn hedged_retry<R, E, T, F>(network: NetworkConfig, task: F) -> Result<R, RetryError<E>>
+where
+    F: Fn(RpcClient) -> T + Send + Sync,
+    T: core::future::Future<Output = RetryResponse<R, E>> + Send,
+    T::Output: Send,
+    E: Send,
+{
+    let Some(hedge_delay) = network.hedge_delay else {
+        return retry(network, task).await;
+    };
+    if network.rpc_endpoints.is_empty() {
+        return Err(RetryError::NoRpcEndpoints);
+    }
+
+    let network = &network;
+    let task = &task;
+    let deadline = Deadline::new(network.deadline);
+    let endpoints = network.health.order_endpoints(&network.rpc_endpoints);
+    let count = endpoints.len();
+    let mut endpoints = endpoints.into_iter().map(|(index, _)| index).enumerate();
+    let start = |(position, index): (usize, usize)| async move {
+        let endpoint = &network.rpc_endpoints[index];
+        debug!(target: RETRY_TARGET, "Sending request to {}", endpoint.url);
+        let has_fallback = position + 1 < count;
+        retry_endpoint(
+            network,
+            index,
+            endpoint,
+            has_fallback,
+            deadline,
+            &mut &*task,
+        )
+        .await
+    };
+
+    let mut in_flight = FuturesUnordered::new();
+    in_flight.extend(endpoints.next().map(start));
+
+    let mut attempts = Vec::new();
+    while !in_flight.is_empty() {
+        let outcome = {
+            let next = in_flight.next();
+            if endpoints.len() > 0 {
+                let hedge_timer = std::pin::pin!(tokio::time::sleep(hedge_delay));
+                match futures::future::select(next, hedge_timer).await {
+                    Either::Left((outcome, _)) => outcome,
+                    Either::Right(_) => None,
+                }
+            } else {
+                next.await
+            }
+        };
+
+        match outcome {
+            Some(EndpointOutcome::Ok(result)) => return Ok(result),
+            Some(EndpointOutcome::Critical(error)) => return Err(RetryError::Critical(error)),
+            Some(EndpointOutcome::Transport(error)) => return Err(RetryError::Transport(error)),
+            Some(EndpointOutcome::Exhausted(failed)) => {
+                attempts.extend(failed);
+                in_flight.extend(endpoints.next().map(start));
+            }
+            Some(EndpointOutcome::DeadlineExceeded(failed)) => {
+                attempts.extend(failed);
+                return Err(deadline.exceeded(attempts));
+            }
+            None => {
+                debug!(
+                    target: RETRY_TARGET,
+                    "No response within {:?}, hedging the request", hedge_delay
+                );
+                in_flight.extend(endpoints.next().map(start));
+            }
+        }
+    }
+    Err(RetryError::RetriesExhausted(RetryReport { attempts }))
+}
+
+/// Deadline of the whole request, see [NetworkConfig::deadline].
+#[derive(Debug, Clone, Copy)]
+struct Deadline {
+    duration: std::time::Duration,
+    at: Option<tokio::time::Instant>,
+}
+
+impl Deadline {
+    fn new(duration: Option<std::time::Duration>) -> Self {
+        Self {
+            duration: duration.unwrap_or_default(),
+            at: duration.map(|duration| tokio::time::Instant::now() + duration),
+        }
+    }
+
+    fn remaining(&self) -> Option<std::time::Duration> {
+        self.at
+            .map(|at| at.saturating_duration_since(tokio::time::Instant::now()))
+    }
+
+    /// Returns `true` if the deadline passes before the given duration elapses.
+    fn expires_within(&self, duration: std::time::Duration) -> bool {
+        self.remaining()
+            .is_some_and(|remaining| remaining <= duration)
+    }
+
+    const fn exceeded<E>(&self, attempts: Vec<FailedAttempt<E>>) -> RetryError<E> {
+        RetryError::DeadlineExceeded {
+            deadline: self.duration,
+            report: RetryReport { attempts },
+        }
+    }
+}
+
+enum EndpointOutcome<R, E> {
+    Ok(R),
+    Critical(E),
+    Transport(TransportError),
+    Exhausted(Vec<FailedAttempt<E>>),
+    DeadlineExceeded(Vec<FailedAttempt<E>>),
+}
+
+/// Retries the request on the endpoint.
+///
+/// If the endpoint asked to back off with `Retry-After` for longer than the retry would wait,
+/// the endpoint is given up, unless it's the last one (`has_fallback` is `false`).
+async fn retry_endpoint<R, E, T, F>(
+    network: &NetworkConfig,
+    index: usize,
+    endpoint: &RPCEndpoint,
+    has_fallback: bool,
+    deadline: Deadline,
+    task: &mut F,
+) -> EndpointOutcome<R, E>
+where
+    F: FnMut(RpcClient) -> T + Send,
+    T: core::future::Future<Output = RetryResponse<R, E>> + Send,
+{
+    let throttled_longer_than = |backoff: std::time::Duration| {
+        network
+            .rate_limiter
+            .throttled_until(&endpoint.url)
+            .map(|until| until.saturating_duration_since(std::time::Instant::now()))
+            .filter(|throttled| has_fallback && *throttled > backoff)
+    };
+    if let Some(throttled) = throttled_longer_than(std::time::Duration::ZERO) {
+        debug!(
+            target: RETRY_TARGET,
+            "Endpoint {} is throttled for {:?}, moving on to the next one", endpoint.url, throttled
+        );
+        return EndpointOutcome::Exhausted(vec![FailedAttempt {
+            endpoint: endpoint.url.clone(),
+            error: AttemptError::Throttled(throttled),
+        }]);
+    }
+
+    let client = network.rpc_client(index);
+    let mut failed = Vec::new();
+    let mut sleep = None;
+    for retry in 0..endpoint.retries {
+        // Waits if the endpoint is over its budget or asked us to back off with `Retry-After`
+        let acquire = network
+            .rate_limiter
+            .acquire(&endpoint.url, endpoint.rate_limit);
+        match deadline.remaining() {
+            Some(remaining) => {
+                if tokio::time::timeout(remaining, acquire).await.is_err() {
+                    return EndpointOutcome::DeadlineExceeded(failed);
+                }
+            }
+            None => acquire.await,
+        }
+
+        let timeout = match (endpoint.attempt_timeout, deadline.remaining()) {
+            (Some(timeout), Some(remaining)) => Some(timeout.min(remaining)),
+            (timeout, remaining) => timeout.or(remaining),
+        };
+        if timeout.is_some_and(|timeout| timeout.is_zero()) {
+            return EndpointOutcome::DeadlineExceeded(failed);
+        }
+
+        let started_at = std::time::Instant::now();
+        let attempt = async {
+            match verify_attempt(network, &client).await {
+                Ok(()) => task(client.clone()).await.into(),
+                Err(response) => response,
+            }
+        };
+        let result = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, attempt)
+                .await
+                .unwrap_or(AttemptResponse::Retry(AttemptError::Timeout(timeout))),
+            None => attempt.await,
+        };
+        let (error, endpoint_failed) = match result {
+            AttemptResponse::Ok(result) => {
+                network
+                    .health
+                    .record_success(&endpoint.url, started_at.elapsed());
+                return EndpointOutcome::Ok(result);
+            }
+            AttemptResponse::Critical(error) => return EndpointOutcome::Critical(error),
+            AttemptResponse::Transport(error) => return EndpointOutcome::Transport(error),
+            AttemptResponse::Mismatch(verification) => {
+                failed.push(FailedAttempt {
+                    endpoint: endpoint.url.clone(),
+                    error: AttemptError::VerificationFailed(verification),
+                });
+                return EndpointOutcome::Exhausted(failed);
+            }
+            AttemptResponse::Retry(error) => (error, true),
+            AttemptResponse::Pending(error) => (AttemptError::Error(error), false),
+        };
+        let timed_out = matches!(error, AttemptError::Timeout(_));
+        failed.push(FailedAttempt {
+            endpoint: endpoint.url.clone(),
+            error,
+        });
+        let attempt_failed = |retry_in| AttemptFailedEvent {
+            endpoint: &endpoint.url,
+            attempt: retry + 1,
+            timed_out,
+            retry_in,
+        };
+
+        if deadline.expires_within(std::time::Duration::ZERO) {
+            network.observe(|observer| observer.on_attempt_failed(&attempt_failed(None)));
+            return EndpointOutcome::DeadlineExceeded(failed);
+        }
+
+        // The breaker affects the next requests, this one still uses all its retries
+        if endpoint_failed
+            && network
+                .health
+                .record_failure(&endpoint.url, endpoint.circuit_breaker.as_ref())
+        {
+            warn!(
+                target: RETRY_TARGET,
+                "Endpoint {} is considered unhealthy and will be skipped by the next requests", endpoint.url
+            );
+        }
+        if retry + 1 == endpoint.retries {
+            network.observe(|observer| observer.on_attempt_failed(&attempt_failed(None)));
+            break;
+        }
+
+        let next_sleep = endpoint.next_sleep_duration(retry as usize, sleep);
+        if deadline.expires_within(next_sleep) {
+            network.observe(|observer| observer.on_attempt_failed(&attempt_failed(None)));
+            return EndpointOutcome::DeadlineExceeded(failed);
+        }
+        if let Some(throttled) = throttled_longer_than(next_sleep) {
+            debug!(
+                target: RETRY_TARGET,
+                "Endpoint {} asked to back off for {:?}, moving on to the next one", endpoint.url, throttled
+            );
+            network.observe(|observer| observer.on_attempt_failed(&attempt_failed(None)));
+            break;
+        }
+        network.observe(|observer| observer.on_attempt_failed(&attempt_failed(Some(next_sleep))));
+        debug!(
+            target: RETRY_TARGET,
+            "Retrying request to {} in {:?} (attempt {})",
+            endpoint.url,
+            next_sleep,
+            retry + 1
+        );
+        tokio::time::sleep(next_sleep).await;
+        sleep = Some(next_sleep);
+    }
+    EndpointOutcome::Exhausted(failed)
+}
+
+/// Verifies the chain identity of the endpoint before the attempt, if it's required and wasn't done yet.
+///
+/// The failed `status` request is retried as any other failed request,
+/// while the endpoint that serves the other chain is given up.
+async fn verify_attempt<R, E>(
+    network: &NetworkConfig,
+    client: &RpcClient,
+) -> Result<(), AttemptResponse<R, E>> {
+    let verification = match network.health.verification(&client.endpoint().url) {
+        Some(verification) => verification,
+        None if network.verify_chain_identity => verification::verify_endpoint(network, client)
+            .await
+            .map_err(AttemptResponse::Transport)?,
+        None => return Ok(()),
+    };
+    match verification {
+        EndpointVerification::Verified => Ok(()),
+        EndpointVerification::Unavailable(_) => Err(AttemptResponse::Retry(
+            AttemptError::VerificationFailed(verification),
+        )),
+        mismatch => Err(AttemptResponse::Mismatch(mismatch)),
+    }
+}
+
+/// [RetryResponse] of the single attempt, where the retryable error can also be a timeout
+/// or the failed chain identity verification.
+enum AttemptResponse<R, E> {
+    Ok(R),
+    Retry(AttemptError<E>),
+    Pending(E),
+    Critical(E),
+    Transport(TransportError),
+    /// The endpoint serves the other chain.
+    Mismatch(EndpointVerification),
+}
+
+impl<R, E> From<RetryResponse<R, E>> for AttemptResponse<R, E> {
+    fn from(response: RetryResponse<R, E>) -> Self {
+        match response {
+            RetryResponse::Ok(result) => Self::Ok(result),
+            RetryResponse::Retry(error) => Self::Retry(AttemptError::Error(error)),
+            RetryResponse::Pending(error) => Self::Pending(error),
+            RetryResponse::Critical(error) => Self::Critical(error),
+            RetryResponse::Transport(error) => Self::Transport(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use near_jsonrpc_client::methods::gas_price::RpcGasPriceRequest;
+
+    use crate::testing::{network_with_endpoints, rpc_result, ScriptedTransport};
+
+    use super::*;
+
+    fn network(endpoints: &[&str]) -> NetworkConfig {
+        network_with_endpoints(endpoints, ScriptedTransport::default()).0
+    }
+
+    #[test]
+    fn sleep_duration_is_capped() {
+        let endpoint = RPCEndpoint::testnet().with_max_sleep(Duration::from_millis(50));
+        assert_eq!(endpoint.get_sleep_duration(2), Duration::from_millis(40));
+        assert_eq!(endpoint.get_sleep_duration(3), Duration::from_millis(50));
+        assert_eq!(endpoint.get_sleep_duration(100), Duration::from_millis(50));
+
+        let endpoint = endpoint.with_jitter(Jitter::Decorrelated);
+        for _ in 0..10 {
+            let sleep = endpoint.next_sleep_duration(5, Some(Duration::from_millis(40)));
+            assert!(sleep >= Duration::from_millis(10) && sleep <= Duration::from_millis(50));
+        }
+    }
+
+    #[tokio::test]
+    async fn report_contains_all_attempts() {
+        let network = network(&["http://a.near", "http://b.near"]);
+        let error = retry(network, |_| async {
+            RetryResponse::<(), _>::Retry("failed")
+        })
+        .await
+        .unwrap_err();
+
+        let RetryError::RetriesExhausted(report) = error else {
+            panic!("Unexpected error: {error:?}");
+        };
+        assert_eq!(report.attempts.len(), 6);
+        assert_eq!(
+            report.endpoints(),
+            vec![
+                &"http://a.near".parse::<url::Url>().unwrap(),
+                &"http://b.near".parse::<url::Url>().unwrap()
+            ]
+        );
+        assert_eq!(report.last_error(), Some(&"failed"));
+    }
+
+    #[tokio::test]
+    async fn breaker_counts_only_endpoint_failures() {
+        let mut network = network(&["http://a.near"]);
+        network.rpc_endpoints[0] = network.rpc_endpoints[0]
+            .clone()
+            .with_retries(3)
+            .with_circuit_breaker(CircuitBreaker::new(1, Duration::from_secs(60)));
+        let url = network.rpc_endpoints[0].url.clone();
+
+        retry(network.clone(), |_| async {
+            RetryResponse::<(), _>::Pending("not executed yet")
+        })
+        .await
+        .unwrap_err();
+        assert!(network.health.endpoint(&url).is_none());
+
+        let mut calls = 0;
+        retry(network.clone(), |_| {
+            calls += 1;
+            async { RetryResponse::<(), _>::Retry("unavailable") }
+        })
+        .await
+        .unwrap_err();
+        // The tripped breaker doesn't cut the retries of the request
+        assert_eq!(calls, 3);
+        assert!(!network
+            .health
+            .endpoint(&url)
+            .unwrap()
+            .is_available(std::time::Instant::now()));
+    }
+
+    #[tokio::test]
+    async fn deadline_stops_retries() {
+        let mut network = network(&["http://a.near"]);
+        network.rpc_endpoints[0] = network.rpc_endpoints[0]
+            .clone()
+            .with_attempt_timeout(Duration::from_millis(20));
+        network.deadline = Some(Duration::from_millis(30));
+
+        let error = retry(network, |_| async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            RetryResponse::<(), ()>::Ok(())
+        })
+        .await
+        .unwrap_err();
+
+        let RetryError::DeadlineExceeded { report, .. } = error else {
+            panic!("Unexpected error: {error:?}");
+        };
+        assert!(matches!(
+            report.attempts[0].error,
+            AttemptError::Timeout(timeout) if timeout == Duration::from_millis(20)
+        ));
+    }
+
+    #[tokio::test]
+    async fn throttled_endpoint_falls_through() {
+        let (mut network, transport) = network_with_endpoints(
+            &["http://a.near", "http://b.near"],
+            ScriptedTransport::new(|endpoint, _| {
+                if endpoint.url.as_str() == "http://a.near/" {
+                    RpcResponse {
+                        retry_after: Some(Duration::from_secs(3600)),
+                        ..RpcResponse::status(reqwest::StatusCode::TOO_MANY_REQUESTS)
+                    }
+                } else {
+                    rpc_result(serde_json::json!({"gas_price": "1"}))
+                }
+            }),
+        );
+        for endpoint in &mut network.rpc_endpoints {
+            endpoint.max_sleep = Some(Duration::from_secs(5));
+        }
+        let gas_price = || {
+            retry(network.clone(), |client| async move {
+                RetryResponse::from(client.call(RpcGasPriceRequest { block_id: None }).await)
+            })
+        };
+
+        let started_at = std::time::Instant::now();
+        assert_eq!(gas_price().await.unwrap().gas_price, 1);
+        assert!(started_at.elapsed() < Duration::from_secs(1));
+        assert_eq!(transport.requests_to("http://a.near"), 1);
+        assert_eq!(transport.requests_to("http://b.near"), 1);
+
+        // Retry-After is capped by the max sleep
+        let throttled_until = network
+            .rate_limiter
+            .throttled_until(&"http://a.near".parse().unwrap())
+            .unwrap();
+        assert!(throttled_until <= std::time::Instant::now() + Duration::from_secs(5));
+
+        // The next request doesn't wait for the throttled endpoint
+        assert_eq!(gas_price().await.unwrap().gas_price, 1);
+        assert_eq!(transport.requests_to("http://a.near"), 1);
+        assert_eq!(transport.requests_to("http://b.near"), 2);
+    }
+
+    #[tokio::test]
+    async fn deadline_bounds_throttled_wait() {
+        let mut network = network(&["http://a.near"]);
+        network.deadline = Some(Duration::from_millis(50));
+        network
+            .rate_limiter
+            .throttle(&network.rpc_endpoints[0].url, Duration::from_secs(3600));
+
+        let started_at = std::time::Instant::now();
+        let error = retry(network, |_| async { RetryResponse::<(), ()>::Ok(()) })
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, RetryError::DeadlineExceeded { .. }),
+            "{error:?}"
+        );
+        assert!(started_at.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn http_client_keeps_custom_transport() {
+        let network = NetworkConfig::testnet()
+            .with_transport(ReplayTransport::new(Cassette::default()))
+            .with_http_client(reqwest::Client::new());
+
+        let error = crate::Chain::block_number()
+            .fetch_from(&network)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("not recorded"), "{error}");
+    }
+}
@@ -0,0 +1,185 @@
+use std::time::Duration;
+
+use near_jsonrpc_client::errors::{
+    JsonRpcError, JsonRpcServerError, JsonRpcServerResponseStatusError,
+};
+
+use crate::errors::RpcCallError;
+
+/// Observer of the RPC requests made with the [NetworkConfig](super::NetworkConfig), e.g. to feed metrics.
+///
+/// All the methods are called synchronously on the request path, so they should be cheap.
+/// See `MetricsObserver` (`metrics` feature) for the implementation backed by the [metrics](https://docs.rs/metrics) crate.
+pub trait RpcObserver: std::fmt::Debug + Send + Sync {
+    /// Called after every JSON-RPC call to the endpoint.
+    fn on_rpc_call(&self, _event: &RpcCallEvent<'_>) {}
+
+    /// Called after every failed attempt of the retried request, including the timed out ones.
+    fn on_attempt_failed(&self, _event: &AttemptFailedEvent<'_>) {}
+
+    /// Called once the query or transaction is done, with all the retries and endpoints included.
+    fn on_operation(&self, _event: &OperationEvent) {}
+}
+
+#[derive(Debug, Clone)]
+pub struct RpcCallEvent<'a> {
+    pub endpoint: &'a url::Url,
+    pub method: &'a str,
+    pub latency: Duration,
+    pub error: Option<RpcErrorKind>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AttemptFailedEvent<'a> {
+    pub endpoint: &'a url::Url,
+    /// Number of the failed attempt on this endpoint, starting from 1.
+    pub attempt: u8,
+    pub timed_out: bool,
+    /// Sleep before the next attempt on this endpoint, or `None` if the endpoint is given up.
+    pub retry_in: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OperationEvent {
+    pub kind: OperationKind,
+    pub latency: Duration,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    Query,
+    MultiQuery,
+    SendTransaction,
+}
+
+impl OperationKind {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::MultiQuery => "multi_query",
+            Self::SendTransaction => "send_transaction",
+        }
+    }
+}
+
+/// Kind of the failed JSON-RPC call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcErrorKind {
+    /// The request wasn't delivered, or the response couldn't be parsed.
+    Transport,
+    /// The endpoint responded with a non-200 HTTP status.
+    ResponseStatus(u16),
+    /// The method handler returned an error, e.g. unknown account.
+    Handler,
+    RequestValidation,
+    Internal,
+    NonContextual,
+}
+
+impl RpcErrorKind {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Transport => "transport",
+            Self::ResponseStatus(_) => "response_status",
+            Self::Handler => "handler",
+            Self::RequestValidation => "request_validation",
+            Self::Internal => "internal",
+            Self::NonContextual => "non_contextual",
+        }
+    }
+}
+
+impl<E> From<&JsonRpcError<E>> for RpcErrorKind {
+    fn from(error: &JsonRpcError<E>) -> Self {
+        match error {
+            JsonRpcError::TransportError(_) => Self::Transport,
+            JsonRpcError::ServerError(error) => match error {
+                JsonRpcServerError::HandlerError(_) => Self::Handler,
+                JsonRpcServerError::RequestValidationError(_) => Self::RequestValidation,
+                JsonRpcServerError::InternalError { .. } => Self::Internal,
+                JsonRpcServerError::NonContextualError(_) => Self::NonContextual,
+                JsonRpcServerError::ResponseStatusError(status) => {
+                    Self::ResponseStatus(match status {
+                        JsonRpcServerResponseStatusError::Unauthorized => 401,
+                        JsonRpcServerResponseStatusError::TooManyRequests => 429,
+                        JsonRpcServerResponseStatusError::BadRequest => 400,
+                        JsonRpcServerResponseStatusError::TimeoutError => 408,
+                        JsonRpcServerResponseStatusError::ServiceUnavailable => 503,
+                        JsonRpcServerResponseStatusError::Unexpected { status } => status.as_u16(),
+                    })
+                }
+            },
+        }
+    }
+}
+
+impl<E> From<&RpcCallError<E>> for RpcErrorKind {
+    fn from(error: &RpcCallError<E>) -> Self {
+        match error {
+            RpcCallError::JsonRpcError(error) => Self::from(error),
+            RpcCallError::TransportError(_) => Self::Transport,
+        }
+    }
+}
+
+/// [RpcObserver] that records the events with the [metrics](https://docs.rs/metrics) crate.
+///
+/// Recorded metrics:
+/// - `near_api_rpc_calls_total` counter and `near_api_rpc_call_duration_seconds` histogram,
+///   labeled by `endpoint`, `method` and `result`;
+/// - `near_api_rpc_failed_attempts_total` counter, labeled by `endpoint`, `reason` and `retried`;
+/// - `near_api_operations_total` counter and `near_api_operation_duration_seconds` histogram,
+///   labeled by `operation` and `result`.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MetricsObserver;
+
+#[cfg(feature = "metrics")]
+impl RpcObserver for MetricsObserver {
+    fn on_rpc_call(&self, event: &RpcCallEvent<'_>) {
+        let endpoint = event.endpoint.to_string();
+        let method = event.method.to_string();
+        let result = event.error.map_or("success", |error| error.as_str());
+        metrics::counter!(
+            "near_api_rpc_calls_total",
+            "endpoint" => endpoint.clone(),
+            "method" => method.clone(),
+            "result" => result
+        )
+        .increment(1);
+        metrics::histogram!(
+            "near_api_rpc_call_duration_seconds",
+            "endpoint" => endpoint,
+            "method" => method,
+            "result" => result
+        )
+        .record(event.latency.as_secs_f64());
+    }
+
+    fn on_attempt_failed(&self, event: &AttemptFailedEvent<'_>) {
+        metrics::counter!(
+            "near_api_rpc_failed_attempts_total",
+            "endpoint" => event.endpoint.to_string(),
+            "reason" => if event.timed_out { "timeout" } else { "error" },
+            "retried" => if event.retry_in.is_some() { "true" } else { "false" }
+        )
+        .increment(1);
+    }
+
+    fn on_operation(&self, event: &OperationEvent) {
+        let result = if event.success { "success" } else { "failure" };
+        metrics::counter!(
+            "near_api_operations_total",
+            "operation" => event.kind.as_str(),
+            "result" => result
+        )
+        .increment(1);
+        metrics::histogram!(
+            "near_api_operation_duration_seconds",
+            "operation" => event.kind.as_str(),
+            "result" => result
+        )
+        .record(event.latency.as_secs_f64());
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tracing::{debug, warn};
+
+const RATE_LIMIT_TARGET: &str = "near_api::rpc::rate_limit";
+
+/// Client-side request budget of the [RPCEndpoint](super::RPCEndpoint).
+///
+/// The budget is enforced by a token bucket: it holds up to `burst` requests,
+/// and is refilled with `requests_per_second` requests every second.
+/// Requests that exceed the budget wait for the next token instead of being sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RateLimit {
+    pub requests_per_second: u32,
+    pub burst: u32,
+}
+
+impl RateLimit {
+    pub const fn new(requests_per_second: u32, burst: u32) -> Self {
+        Self {
+            requests_per_second,
+            burst,
+        }
+    }
+
+    /// Rate limit with the burst equal to the number of requests per second.
+    pub const fn per_second(requests_per_second: u32) -> Self {
+        Self::new(requests_per_second, requests_per_second)
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+    throttled_until: Option<Instant>,
+}
+
+impl Bucket {
+    fn new(now: Instant, limit: Option<RateLimit>) -> Self {
+        Self {
+            tokens: limit.map_or(0.0, |limit| limit.burst.max(1) as f64),
+            updated_at: now,
+            throttled_until: None,
+        }
+    }
+
+    fn throttle_until(&mut self, until: Instant) {
+        self.throttled_until = Some(self.throttled_until.map_or(until, |old| old.max(until)));
+    }
+
+    /// Takes a token, or returns how long to wait before trying again.
+    fn try_acquire(&mut self, limit: Option<RateLimit>, now: Instant) -> Option<Duration> {
+        if let Some(until) = self.throttled_until {
+            if until > now {
+                return Some(until - now);
+            }
+            self.throttled_until = None;
+        }
+
+        let limit = limit?;
+        let rate = limit.requests_per_second.max(1) as f64;
+        let burst = limit.burst.max(1) as f64;
+
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = elapsed.mul_add(rate, self.tokens).min(burst);
+        self.updated_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        }
+    }
+}
+
+/// Request budgets of the RPC endpoints shared between all the clones of the [NetworkConfig](super::NetworkConfig).
+///
+/// The state is keyed by the endpoint URL, so concurrent requests to the same endpoint
+/// are queued locally even if they are sent through the different configs cloned from one.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    buckets: Arc<Mutex<HashMap<url::Url, Bucket>>>,
+}
+
+impl RateLimiter {
+    /// Returns the instant until which the endpoint asked us to back off with `429 Too Many Requests`.
+    pub fn throttled_until(&self, url: &url::Url) -> Option<Instant> {
+        self.lock()
+            .get(url)
+            .and_then(|bucket| bucket.throttled_until)
+            .filter(|until| *until > Instant::now())
+    }
+
+    /// Waits until the request to the endpoint fits into its budget.
+    pub(crate) async fn acquire(&self, url: &url::Url, limit: Option<RateLimit>) {
+        while let Some(wait) = self.try_acquire(url, limit) {
+            debug!(
+                target: RATE_LIMIT_TARGET,
+                "Request to {url} is over the budget, waiting for {wait:?}"
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Pauses all the requests to the endpoint for the given duration.
+    pub(crate) fn throttle(&self, url: &url::Url, duration: Duration) {
+        warn!(
+            target: RATE_LIMIT_TARGET,
+            "{url} responded with 429 Too Many Requests, pausing requests for {duration:?}"
+        );
+        let now = Instant::now();
+        self.lock()
+            .entry(url.clone())
+            .or_insert_with(|| Bucket::new(now, None))
+            .throttle_until(now + duration);
+    }
+
+    fn try_acquire(&self, url: &url::Url, limit: Option<RateLimit>) -> Option<Duration> {
+        let now = Instant::now();
+        let mut buckets = self.lock();
+        if let Some(bucket) = buckets.get_mut(url) {
+            return bucket.try_acquire(limit, now);
+        }
+        // Endpoints without the budget don't need the bucket until they are throttled
+        let limit = limit?;
+        buckets
+            .entry(url.clone())
+            .or_insert_with(|| Bucket::new(now, Some(limit)))
+            .try_acquire(Some(limit), now)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<url::Url, Bucket>> {
+        self.buckets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_allows_burst_then_waits() {
+        let now = Instant::now();
+        let limit = Some(RateLimit::new(10, 2));
+        let mut bucket = Bucket::new(now, limit);
+
+        assert_eq!(bucket.try_acquire(limit, now), None);
+        assert_eq!(bucket.try_acquire(limit, now), None);
+        let wait = bucket.try_acquire(limit, now).unwrap();
+        assert!(wait <= Duration::from_millis(100));
+
+        assert_eq!(
+            bucket.try_acquire(limit, now + Duration::from_millis(100)),
+            None
+        );
+    }
+
+    #[test]
+    fn throttle_pauses_unlimited_endpoint() {
+        let now = Instant::now();
+        let mut bucket = Bucket::new(now, None);
+        assert_eq!(bucket.try_acquire(None, now), None);
+
+        bucket.throttled_until = Some(now + Duration::from_secs(2));
+        assert_eq!(
+            bucket.try_acquire(None, now + Duration::from_secs(1)),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(bucket.try_acquire(None, now + Duration::from_secs(2)), None);
+    }
+}
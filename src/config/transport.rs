@@ -0,0 +1,203 @@
+use std::{
+    sync::{Arc, OnceLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use crate::errors::TransportError;
+
+use super::RPCEndpoint;
+
+/// HTTP-level response of the JSON-RPC endpoint.
+#[derive(Debug, Clone)]
+pub struct RpcResponse {
+    pub status: reqwest::StatusCode,
+    /// Value of the `Retry-After` header, if the endpoint asked to back off.
+    pub retry_after: Option<Duration>,
+    /// Raw JSON-RPC response message.
+    pub body: Vec<u8>,
+}
+
+impl RpcResponse {
+    /// Successful response with the given JSON-RPC message.
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: reqwest::StatusCode::OK,
+            retry_after: None,
+            body: body.into(),
+        }
+    }
+
+    /// Empty response with the given status.
+    pub const fn status(status: reqwest::StatusCode) -> Self {
+        Self {
+            status,
+            retry_after: None,
+            body: Vec::new(),
+        }
+    }
+}
+
+/// Transport that delivers JSON-RPC messages to the [RPCEndpoint].
+///
+/// All the requests made by the library go through the transport of the [NetworkConfig](super::NetworkConfig),
+/// so it can be replaced to customize the HTTP client or to serve the requests from memory in tests.
+/// The status of the response is interpreted the same way as the `near-jsonrpc-client` does.
+#[async_trait::async_trait]
+pub trait RpcTransport: std::fmt::Debug + Send + Sync {
+    async fn send(
+        &self,
+        endpoint: &RPCEndpoint,
+        request: &serde_json::Value,
+    ) -> Result<RpcResponse, TransportError>;
+
+    /// Returns the same transport that sends the requests with the given HTTP client,
+    /// or `None` if the transport doesn't send the requests over HTTP.
+    ///
+    /// Used by [NetworkConfig::with_http_client](super::NetworkConfig::with_http_client).
+    fn with_http_client(&self, _client: reqwest::Client) -> Option<Arc<dyn RpcTransport>> {
+        None
+    }
+}
+
+/// Default [RpcTransport] that sends the requests over HTTP with [reqwest].
+///
+/// It sends the [RPCEndpoint::api_key] in the `x-api-key` header.
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    client: reqwest::Client,
+    headers: HeaderMap,
+}
+
+impl HttpTransport {
+    /// Transport with the custom client, e.g. with a proxy, client certificate or timeouts.
+    pub fn new(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Adds the header to every request.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+}
+
+impl Default for HttpTransport {
+    /// Transport with the client shared by the whole process.
+    fn default() -> Self {
+        Self::new(shared_client().clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl RpcTransport for HttpTransport {
+    async fn send(
+        &self,
+        endpoint: &RPCEndpoint,
+        request: &serde_json::Value,
+    ) -> Result<RpcResponse, TransportError> {
+        let mut headers = self.headers.clone();
+        if let Some(api_key) = &endpoint.api_key {
+            if let Ok(value) = HeaderValue::from_bytes(api_key.0.as_bytes()) {
+                headers.insert(near_jsonrpc_client::auth::ApiKey::HEADER_NAME, value);
+            }
+        }
+
+        let response = self
+            .client
+            .post(endpoint.url.clone())
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            )
+            .headers(headers)
+            .json(request)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let retry_after = retry_after(response.headers());
+        let body = response.bytes().await?.to_vec();
+        Ok(RpcResponse {
+            status,
+            retry_after,
+            body,
+        })
+    }
+
+    fn with_http_client(&self, client: reqwest::Client) -> Option<Arc<dyn RpcTransport>> {
+        Some(Arc::new(Self {
+            client,
+            headers: self.headers.clone(),
+        }))
+    }
+}
+
+/// HTTP client shared by the whole process, so the connections are kept alive and reused
+/// across the requests and [NetworkConfig](super::NetworkConfig) instances.
+pub fn shared_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .tcp_keepalive(Duration::from_secs(60))
+            .build()
+            .expect("Failed to build the HTTP client")
+    })
+}
+
+/// Parses the `Retry-After` header given in seconds or as the HTTP date.
+///
+/// Only the IMF-fixdate form of the HTTP date, which the servers must send, is supported.
+/// The date in the past means no delay.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    retry_after_at(value, SystemTime::now())
+}
+
+fn retry_after_at(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let date = UNIX_EPOCH + Duration::from_secs(date.timestamp().try_into().ok()?);
+    Some(date.duration_since(now).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(reqwest::header::RETRY_AFTER, "3".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(3)));
+
+        headers.insert(
+            reqwest::header::RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(reqwest::header::RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn parse_retry_after_date() {
+        // 10 seconds before the date
+        let now = UNIX_EPOCH + Duration::from_secs(1_445_412_470);
+        assert_eq!(
+            retry_after_at("Wed, 21 Oct 2015 07:28:00 GMT", now),
+            Some(Duration::from_secs(10))
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use near_jsonrpc_client::methods::status::RpcStatusRequest;
+use near_primitives::hash::CryptoHash;
+use tracing::{debug, warn};
+
+use super::{NetworkConfig, RpcClient};
+use crate::errors::{RpcCallError, TransportError};
+
+const VERIFICATION_TARGET: &str = "near_api::rpc::verification";
+
+/// Result of the chain identity verification of the RPC endpoint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EndpointVerification {
+    /// The endpoint serves the expected chain.
+    Verified,
+    /// The endpoint serves a chain with the different `chain_id` than the [NetworkConfig::network_name].
+    ChainIdMismatch { expected: String, actual: String },
+    /// The endpoint serves a chain with the different genesis than the [NetworkConfig::expected_genesis_hash].
+    GenesisHashMismatch {
+        expected: CryptoHash,
+        actual: CryptoHash,
+    },
+    /// The `status` request failed, so the endpoint identity is unknown.
+    Unavailable(String),
+}
+
+impl EndpointVerification {
+    pub const fn is_verified(&self) -> bool {
+        matches!(self, Self::Verified)
+    }
+}
+
+impl std::fmt::Display for EndpointVerification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Verified => write!(f, "Endpoint is verified"),
+            Self::ChainIdMismatch { expected, actual } => write!(
+                f,
+                "Endpoint serves <{actual}> chain, but <{expected}> is expected"
+            ),
+            Self::GenesisHashMismatch { expected, actual } => write!(
+                f,
+                "Endpoint genesis hash is {actual}, but {expected} is expected"
+            ),
+            Self::Unavailable(reason) => {
+                write!(f, "Failed to fetch the endpoint status: {reason}")
+            }
+        }
+    }
+}
+
+/// Returns the verification result of the endpoint, fetching its status if it wasn't verified yet.
+///
+/// Verified and mismatching endpoints are remembered in the [RpcHealth](super::RpcHealth),
+/// while the unavailable ones are verified again on the next request.
+/// The failure of the custom transport is returned as is, as it's not specific to the endpoint.
+pub async fn verify_endpoint(
+    network: &NetworkConfig,
+    client: &RpcClient,
+) -> Result<EndpointVerification, TransportError> {
+    let url = &client.endpoint().url;
+    if let Some(verification) = network.health.verification(url) {
+        return Ok(verification);
+    }
+
+    // The `status` request counts against the budget of the endpoint as any other request
+    network
+        .rate_limiter
+        .acquire(url, client.endpoint().rate_limit)
+        .await;
+    let verification = match client.call(RpcStatusRequest).await {
+        Ok(status) => check_identity(network, &status.chain_id, status.genesis_hash),
+        Err(RpcCallError::TransportError(err)) => return Err(err),
+        Err(RpcCallError::JsonRpcError(err)) => EndpointVerification::Unavailable(err.to_string()),
+    };
+
+    match &verification {
+        EndpointVerification::Verified => {
+            debug!(target: VERIFICATION_TARGET, "Endpoint {url} is verified");
+        }
+        EndpointVerification::Unavailable(reason) => {
+            warn!(target: VERIFICATION_TARGET, "Failed to verify endpoint {url}: {reason}");
+            return Ok(verification);
+        }
+        mismatch => {
+            warn!(target: VERIFICATION_TARGET, "Endpoint {url} is excluded: {mismatch}");
+        }
+    }
+    network
+        .health
+        .record_verification(url, verification.clone());
+    Ok(verification)
+}
+
+fn check_identity(
+    network: &NetworkConfig,
+    chain_id: &str,
+    genesis_hash: CryptoHash,
+) -> EndpointVerification {
+    match network.expected_genesis_hash {
+        Some(expected) if expected != genesis_hash => EndpointVerification::GenesisHashMismatch {
+            expected,
+            actual: genesis_hash,
+        },
+        Some(_) => EndpointVerification::Verified,
+        None if chain_id != network.network_name => EndpointVerification::ChainIdMismatch {
+            expected: network.network_name.clone(),
+            actual: chain_id.to_string(),
+        },
+        None => EndpointVerification::Verified,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_jsonrpc_client::methods::gas_price::RpcGasPriceRequest;
+
+    use crate::{
+        config::{retry, RetryResponse, RpcResponse},
+        testing::{network, rpc_result, status, ScriptedTransport},
+    };
+
+    use super::*;
+
+    #[test]
+    fn genesis_hash_takes_precedence_over_chain_id() {
+        let mut network = NetworkConfig::testnet();
+        assert_eq!(
+            check_identity(&network, "mainnet", CryptoHash::default()),
+            EndpointVerification::ChainIdMismatch {
+                expected: "testnet".to_string(),
+                actual: "mainnet".to_string()
+            }
+        );
+        assert!(check_identity(&network, "testnet", CryptoHash::default()).is_verified());
+
+        let genesis_hash = CryptoHash::hash_bytes(b"genesis");
+        network.expected_genesis_hash = Some(genesis_hash);
+        assert!(check_identity(&network, "localnet", genesis_hash).is_verified());
+        assert!(matches!(
+            check_identity(&network, "testnet", CryptoHash::default()),
+            EndpointVerification::GenesisHashMismatch { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn failed_verification_is_retried() {
+        let (mut network, transport) = network(ScriptedTransport::responses([
+            RpcResponse::status(reqwest::StatusCode::SERVICE_UNAVAILABLE),
+            status("testnet"),
+            rpc_result(serde_json::json!({"gas_price": "1"})),
+        ]));
+        network.verify_chain_identity = true;
+
+        let gas_price = retry(network.clone(), |client| async move {
+            RetryResponse::from(client.call(RpcGasPriceRequest { block_id: None }).await)
+        })
+        .await
+        .unwrap();
+        assert_eq!(gas_price.gas_price, 1);
+
+        let methods: Vec<_> = transport
+            .requests()
+            .into_iter()
+            .map(|request| request["method"].clone())
+            .collect();
+        assert_eq!(methods, ["status", "status", "gas_price"]);
+        let url = &network.rpc_endpoints[0].url;
+        assert_eq!(
+            network.health.verification(url),
+            Some(EndpointVerification::Verified)
+        );
+        assert_eq!(network.health.endpoint(url).unwrap().total_failures, 1);
+    }
+}
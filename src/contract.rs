@@ -1,26 +1,47 @@
 use std::{marker::PhantomData, sync::Arc};
 
+use borsh::BorshDeserialize;
 use near_gas::NearGas;
 
+use futures::{
+    future::BoxFuture, stream::FuturesUnordered, FutureExt, Stream, StreamExt, TryStreamExt,
+};
+use near_jsonrpc_client::{
+    errors::{JsonRpcError, JsonRpcServerError},
+    methods::query::{RpcQueryError, RpcQueryRequest},
+};
 use near_primitives::{
     action::{Action, DeployContractAction, FunctionCallAction},
-    types::{AccountId, BlockReference, StoreKey},
+    block::Block,
+    block_header::BlockHeader,
+    shard_layout::account_id_to_shard_id,
+    sharding::ShardChunkHeader,
+    types::{AccountId, BlockId, BlockReference, StoreKey},
+    views::{StateItem, ViewStateResult},
 };
 use near_token::NearToken;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::{debug, info, warn};
 
 use crate::{
+    chain::Chain,
     common::{
+        proof::verify_state_proof,
         query::{
-            CallResultHandler, PostprocessHandler, QueryBuilder, SimpleQuery, ViewCodeHandler,
-            ViewStateHandler,
+            CallResultHandler, CollectionHandler, PostprocessHandler, QueryBuilder, SimpleQuery,
+            ViewCodeHandler, ViewStateHandler,
         },
         send::ExecuteSignedTransaction,
     },
-    errors::BuilderError,
+    config::NetworkConfig,
+    errors::{BuilderError, QueryError, RetryError, StateProofError},
     signer::Signer,
     transactions::{ConstructTransaction, Transaction},
-    types::{contract::ContractSourceMetadata, Data},
+    types::{
+        collections::{ContractState, StorageLayout},
+        contract::ContractSourceMetadata,
+        CryptoHash, Data, Verified,
+    },
 };
 
 #[derive(Clone, Debug)]
@@ -101,6 +122,58 @@ impl Contract {
         self.view_storage_with_prefix(vec![])
     }
 
+    /// Reads the contract state with the proof and verifies it against the state root committed to by the block
+    /// with the given hash, so the state doesn't have to be trusted to the RPC node.
+    pub fn view_verified_storage(&self, block_hash: CryptoHash) -> VerifiedStateBuilder {
+        self.view_verified_storage_with_prefix(vec![], block_hash)
+    }
+
+    pub fn view_verified_storage_with_prefix(
+        &self,
+        prefix: Vec<u8>,
+        block_hash: CryptoHash,
+    ) -> VerifiedStateBuilder {
+        VerifiedStateBuilder::new(self.clone(), prefix, block_hash.into())
+    }
+
+    /// Reads the near-sdk collection with the given storage layout, e.g. [LookupMap](crate::types::collections::LookupMap).
+    ///
+    /// The collection must fit into the single `view_state` response.
+    /// Larger ones can be fetched with [Contract::download_state_with_prefix] and decoded with [StorageLayout::decode].
+    pub fn view_collection<Layout>(&self, layout: Layout) -> QueryBuilder<CollectionHandler<Layout>>
+    where
+        Layout: StorageLayout + Send + Sync,
+    {
+        let request = near_primitives::views::QueryRequest::ViewState {
+            account_id: self.0.clone(),
+            prefix: StoreKey::from(layout.prefix().to_vec()),
+            include_proof: false,
+        };
+
+        QueryBuilder::new(
+            SimpleQuery { request },
+            BlockReference::latest(),
+            CollectionHandler(layout),
+        )
+    }
+
+    /// Reads the contract struct of the near-sdk contract, stored under the `STATE` key.
+    pub fn view_contract_state<T>(&self) -> QueryBuilder<CollectionHandler<ContractState<T>>>
+    where
+        T: BorshDeserialize + Send + Sync,
+    {
+        self.view_collection(ContractState::new())
+    }
+
+    /// Downloads the whole contract state, even if it's too large to be returned with [Contract::view_storage].
+    pub fn download_state(&self) -> StateDownloadBuilder {
+        self.download_state_with_prefix(vec![])
+    }
+
+    pub fn download_state_with_prefix(&self, prefix: Vec<u8>) -> StateDownloadBuilder {
+        StateDownloadBuilder::new(self.clone(), prefix)
+    }
+
     pub fn contract_source_metadata(
         &self,
     ) -> QueryBuilder<CallResultHandler<ContractSourceMetadata>> {
@@ -239,3 +312,429 @@ impl ContractTransactBuilder {
         })))
     }
 }
+
+const STATE_DOWNLOAD_TARGET: &str = "near_api::contract::state_download";
+
+/// How many `view_state` queries are sent concurrently by default.
+const DEFAULT_STATE_DOWNLOAD_CONCURRENCY: usize = 8;
+
+type StateChunk = Data<Vec<StateItem>>;
+type StateQueryResult = Result<Data<ViewStateResult>, QueryError<RpcQueryRequest>>;
+
+/// Downloads the contract state with the `view_state` queries.
+///
+/// The node refuses to return the state that is larger than its limit, so the refused prefix is split
+/// into 256 longer prefixes, which are queried concurrently, and so on recursively.
+/// All the queries are pinned to the block of the first response, so the state is consistent.
+///
+/// A key that is exactly equal to the split prefix isn't covered by the longer prefixes,
+/// so it's looked up separately with the query of the split prefix, which results are filtered to that key.
+/// The node that applies its limit the same way refuses the lookup as well, as there is no single key query,
+/// and then the key is reported as missing with a warning.
+#[derive(Clone, Debug)]
+pub struct StateDownloadBuilder {
+    contract: Contract,
+    prefix: Vec<u8>,
+    reference: BlockReference,
+    concurrency: usize,
+}
+
+impl StateDownloadBuilder {
+    fn new(contract: Contract, prefix: Vec<u8>) -> Self {
+        Self {
+            contract,
+            prefix,
+            reference: BlockReference::latest(),
+            concurrency: DEFAULT_STATE_DOWNLOAD_CONCURRENCY,
+        }
+    }
+
+    pub fn at(self, reference: impl Into<BlockReference>) -> Self {
+        Self {
+            reference: reference.into(),
+            ..self
+        }
+    }
+
+    /// Sets how many queries are sent concurrently once the state is split.
+    pub fn with_concurrency(self, concurrency: usize) -> Self {
+        Self {
+            concurrency: concurrency.max(1),
+            ..self
+        }
+    }
+
+    /// Streams the state as the chunks of items, one chunk per queried prefix or looked up key, in no particular order.
+    ///
+    /// The stream ends after the first error.
+    pub fn stream_from(
+        self,
+        network: &NetworkConfig,
+    ) -> impl Stream<Item = Result<StateChunk, QueryError<RpcQueryRequest>>> + Send + '_ {
+        let download = StateDownload {
+            network,
+            contract: self.contract,
+            reference: self.reference,
+            pinned: false,
+            concurrency: self.concurrency,
+            pending: vec![StateQuery::Prefix(self.prefix)],
+            in_flight: FuturesUnordered::new(),
+        };
+        futures::stream::unfold(download, StateDownload::next_chunk)
+    }
+
+    /// Downloads the whole state, sorted by key.
+    #[allow(clippy::result_large_err)]
+    pub async fn fetch_from(
+        self,
+        network: &NetworkConfig,
+    ) -> Result<StateChunk, QueryError<RpcQueryRequest>> {
+        let chunks: Vec<_> = self.stream_from(network).try_collect().await?;
+        let (block_height, block_hash) = chunks
+            .first()
+            .map(|chunk| (chunk.block_height, chunk.block_hash))
+            .ok_or(QueryError::InternalErrorNoResponse)?;
+
+        let mut data: Vec<_> = chunks.into_iter().flat_map(|chunk| chunk.data).collect();
+        data.sort_by(|a, b| a.key.as_slice().cmp(b.key.as_slice()));
+        info!(target: STATE_DOWNLOAD_TARGET, "Downloaded {} state items", data.len());
+        Ok(Data {
+            data,
+            block_height,
+            block_hash,
+        })
+    }
+
+    #[cfg(feature = "blocking")]
+    #[allow(clippy::result_large_err)]
+    pub fn fetch_from_blocking(
+        self,
+        network: &NetworkConfig,
+    ) -> Result<StateChunk, crate::errors::BlockingError<QueryError<RpcQueryRequest>>> {
+        crate::blocking::block_on(self.fetch_from(network))
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub async fn fetch_from_mainnet(self) -> Result<StateChunk, QueryError<RpcQueryRequest>> {
+        let network = NetworkConfig::mainnet();
+        self.fetch_from(&network).await
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub async fn fetch_from_testnet(self) -> Result<StateChunk, QueryError<RpcQueryRequest>> {
+        let network = NetworkConfig::testnet();
+        self.fetch_from(&network).await
+    }
+}
+
+struct StateDownload<'a> {
+    network: &'a NetworkConfig,
+    contract: Contract,
+    reference: BlockReference,
+    /// Whether the reference is pinned to the block of the first response.
+    pinned: bool,
+    concurrency: usize,
+    pending: Vec<StateQuery>,
+    in_flight: FuturesUnordered<BoxFuture<'a, (StateQuery, StateQueryResult)>>,
+}
+
+#[derive(Clone, Debug)]
+enum StateQuery {
+    /// All the items with the prefix.
+    Prefix(Vec<u8>),
+    /// The item with the key, if it's stored.
+    Key(Vec<u8>),
+}
+
+impl StateDownload<'_> {
+    async fn next_chunk(
+        mut self,
+    ) -> Option<(Result<StateChunk, QueryError<RpcQueryRequest>>, Self)> {
+        loop {
+            // The first query goes alone, as it pins the block for the rest
+            let concurrency = if self.pinned { self.concurrency } else { 1 };
+            while self.in_flight.len() < concurrency {
+                let Some(query) = self.pending.pop() else {
+                    break;
+                };
+                self.query(query);
+            }
+
+            let (query, result) = self.in_flight.next().await?;
+            let response = match result {
+                Ok(response) => response,
+                Err(err) => match (query, too_large_state_block_hash(&err)) {
+                    (StateQuery::Prefix(prefix), Some(block_hash)) => {
+                        debug!(
+                            target: STATE_DOWNLOAD_TARGET,
+                            "State with prefix {prefix:?} is too large, splitting it"
+                        );
+                        self.pin(block_hash);
+                        self.pending.extend((0..=u8::MAX).rev().map(|byte| {
+                            let mut prefix = prefix.clone();
+                            prefix.push(byte);
+                            StateQuery::Prefix(prefix)
+                        }));
+                        self.pending.push(StateQuery::Key(prefix));
+                        continue;
+                    }
+                    (StateQuery::Key(key), Some(_)) => {
+                        warn!(
+                            target: STATE_DOWNLOAD_TARGET,
+                            "The key {key:?} equal to the split prefix can't be looked up, the state may miss it"
+                        );
+                        continue;
+                    }
+                    (_, None) => {
+                        self.pending.clear();
+                        self.in_flight.clear();
+                        return Some((Err(err), self));
+                    }
+                },
+            };
+
+            self.pin(response.block_hash.into());
+            let mut data = response.data.values;
+            if let StateQuery::Key(key) = query {
+                data.retain(|item| item.key.as_slice() == key.as_slice());
+            }
+            let chunk = Data {
+                data,
+                block_height: response.block_height,
+                block_hash: response.block_hash,
+            };
+            return Some((Ok(chunk), self));
+        }
+    }
+
+    fn query(&self, query: StateQuery) {
+        let (StateQuery::Prefix(prefix) | StateQuery::Key(prefix)) = &query;
+        let request = self
+            .contract
+            .view_storage_with_prefix(prefix.clone())
+            .at(self.reference.clone());
+        let network = self.network;
+        self.in_flight
+            .push(async move { (query, request.fetch_from(network).await) }.boxed());
+    }
+
+    fn pin(&mut self, block_hash: near_primitives::hash::CryptoHash) {
+        if !self.pinned {
+            debug!(target: STATE_DOWNLOAD_TARGET, "State download is pinned to block {block_hash}");
+            self.reference = BlockReference::BlockId(BlockId::Hash(block_hash));
+            self.pinned = true;
+        }
+    }
+}
+
+const fn too_large_state_block_hash(
+    err: &QueryError<RpcQueryRequest>,
+) -> Option<near_primitives::hash::CryptoHash> {
+    match err {
+        QueryError::JsonRpcError(RetryError::Critical(JsonRpcError::ServerError(
+            JsonRpcServerError::HandlerError(RpcQueryError::TooLargeContractState {
+                block_hash,
+                ..
+            }),
+        ))) => Some(*block_hash),
+        _ => None,
+    }
+}
+
+const VERIFIED_STATE_TARGET: &str = "near_api::contract::verified_state";
+
+/// Reads the contract state with the proof, and verifies it against the state root
+/// from the chunk header of the referenced block.
+///
+/// The chunk header commits to the state root before its chunk is applied, so the state is read
+/// at the previous block, and [Verified::verified_at] is the referenced block.
+/// The referenced block must include a new chunk for the shard of the contract.
+///
+/// Only the block hash anchors the state to the chain: the block is checked against it,
+/// and a height or finality would let the node pick any block. So the state is read at the block hash,
+/// which should come from a trusted source, e.g. a light client.
+///
+/// The shard of the contract is taken from the protocol config of the block, which isn't committed to
+/// by the block header. The node can't forge the state with a wrong shard layout, as the proof is still
+/// checked against a state root from the header, but it can point to the chunk of another shard,
+/// where the state of the contract is proven to be empty.
+#[derive(Clone, Debug)]
+pub struct VerifiedStateBuilder {
+    contract: Contract,
+    prefix: Vec<u8>,
+    block_hash: near_primitives::hash::CryptoHash,
+}
+
+impl VerifiedStateBuilder {
+    const fn new(
+        contract: Contract,
+        prefix: Vec<u8>,
+        block_hash: near_primitives::hash::CryptoHash,
+    ) -> Self {
+        Self {
+            contract,
+            prefix,
+            block_hash,
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub async fn fetch_from(
+        self,
+        network: &NetworkConfig,
+    ) -> Result<Data<Verified<ViewStateResult>>, StateProofError> {
+        let block_hash = self.block_hash;
+        let block = Chain::block()
+            .at(BlockReference::BlockId(BlockId::Hash(block_hash)))
+            .fetch_from(network)
+            .await?;
+        let header = BlockHeader::from(block.header);
+        if *header.hash() != block_hash {
+            return Err(StateProofError::InvalidBlockHeader(block_hash));
+        }
+        let chunks: Vec<ShardChunkHeader> = block.chunks.into_iter().map(Into::into).collect();
+        if Block::compute_chunk_headers_root(&chunks).0 != *header.chunk_headers_root() {
+            return Err(StateProofError::InvalidChunkHeaders(block_hash));
+        }
+
+        let shard_layout = Chain::protocol_config()
+            .at(BlockReference::BlockId(BlockId::Hash(block_hash)))
+            .fetch_from(network)
+            .await?
+            .shard_layout;
+        let shard_id = account_id_to_shard_id(&self.contract.0, &shard_layout);
+        let state_root = chunks
+            .iter()
+            .find(|chunk| {
+                chunk.shard_id() == shard_id && chunk.height_included() == header.height()
+            })
+            .ok_or(StateProofError::MissingChunk {
+                block_hash,
+                shard_id,
+            })?
+            .prev_state_root();
+
+        let request = near_primitives::views::QueryRequest::ViewState {
+            account_id: self.contract.0.clone(),
+            prefix: StoreKey::from(self.prefix.clone()),
+            include_proof: true,
+        };
+        let state = QueryBuilder::new(
+            SimpleQuery { request },
+            BlockReference::BlockId(BlockId::Hash(*header.prev_hash())),
+            ViewStateHandler,
+        )
+        .fetch_from(network)
+        .await?;
+
+        verify_state_proof(&state_root, &self.contract.0, &self.prefix, &state.data)?;
+        info!(
+            target: VERIFIED_STATE_TARGET,
+            "Verified {} state items against state root {state_root} of block {block_hash}",
+            state.data.values.len()
+        );
+        Ok(Data {
+            data: Verified {
+                data: state.data,
+                state_root: state_root.into(),
+                verified_at: block_hash.into(),
+            },
+            block_height: state.block_height,
+            block_hash: state.block_hash,
+        })
+    }
+
+    #[cfg(feature = "blocking")]
+    #[allow(clippy::result_large_err)]
+    pub fn fetch_from_blocking(
+        self,
+        network: &NetworkConfig,
+    ) -> Result<Data<Verified<ViewStateResult>>, crate::errors::BlockingError<StateProofError>>
+    {
+        crate::blocking::block_on(self.fetch_from(network))
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub async fn fetch_from_mainnet(
+        self,
+    ) -> Result<Data<Verified<ViewStateResult>>, StateProofError> {
+        let network = NetworkConfig::mainnet();
+        self.fetch_from(&network).await
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub async fn fetch_from_testnet(
+        self,
+    ) -> Result<Data<Verified<ViewStateResult>>, StateProofError> {
+        let network = NetworkConfig::testnet();
+        self.fetch_from(&network).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::hash::CryptoHash;
+    use serde_json::json;
+
+    use crate::testing::{handler_error, network, rpc_result, ScriptedTransport};
+
+    use super::*;
+
+    /// Serves the contract state, refusing the prefixes with more than `limit` items the first time they're requested.
+    fn state_transport(state: Vec<(Vec<u8>, Vec<u8>)>, limit: usize) -> ScriptedTransport {
+        let refused = std::sync::Mutex::new(std::collections::HashSet::new());
+        ScriptedTransport::new(move |_, request| {
+            let prefix: StoreKey =
+                serde_json::from_value(request["params"]["prefix_base64"].clone()).unwrap();
+            let values: Vec<_> = state
+                .iter()
+                .filter(|(key, _)| key.starts_with(&prefix))
+                .map(|(key, value)| StateItem {
+                    key: key.clone().into(),
+                    value: value.clone().into(),
+                })
+                .collect();
+
+            let block_hash = CryptoHash::hash_bytes(b"block").to_string();
+            if values.len() > limit && refused.lock().unwrap().insert(prefix.to_vec()) {
+                handler_error(
+                    "TOO_LARGE_CONTRACT_STATE",
+                    json!({"contract_account_id": "contract.testnet", "block_height": 1, "block_hash": block_hash}),
+                )
+            } else {
+                rpc_result(json!({"values": values, "block_height": 1, "block_hash": block_hash}))
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn large_state_is_split_by_prefixes() {
+        let state: Vec<_> = [&b"a"[..], b"b", b"ba", b"bb", b"bc", b"bca", b"c"]
+            .iter()
+            .map(|key| (key.to_vec(), key.to_ascii_uppercase()))
+            .collect();
+        let (network, transport) = network(state_transport(state.clone(), 2));
+
+        let downloaded = Contract("contract.testnet".parse().unwrap())
+            .download_state()
+            .fetch_from(&network)
+            .await
+            .unwrap();
+        let downloaded: Vec<_> = downloaded
+            .data
+            .into_iter()
+            .map(|item| (item.key.to_vec(), item.value.to_vec()))
+            .collect();
+        assert_eq!(downloaded, state);
+
+        let block_ids: Vec<_> = transport
+            .requests()
+            .into_iter()
+            .map(|request| request["params"]["block_id"].clone())
+            .collect();
+        assert_eq!(block_ids[0], serde_json::Value::Null);
+        assert!(block_ids[1..]
+            .iter()
+            .all(|block_id| *block_id == CryptoHash::hash_bytes(b"block").to_string()));
+    }
+}
@@ -1,6 +1,9 @@
 use near_jsonrpc_client::{
     errors::JsonRpcError,
-    methods::{query::RpcQueryRequest, tx::RpcTransactionError, RpcMethod},
+    methods::{
+        block::RpcBlockRequest, query::RpcQueryRequest, tx::RpcTransactionError,
+        EXPERIMENTAL_protocol_config::RpcProtocolConfigRequest, RpcMethod,
+    },
 };
 use near_jsonrpc_primitives::types::query::QueryResponseKind;
 
@@ -24,8 +27,12 @@ where
     },
     #[error("Failed to deserialize response: {0}")]
     DeserializeError(#[from] serde_json::Error),
+    #[error("Failed to deserialize borsh data: {0}")]
+    BorshDeserializeError(#[from] std::io::Error),
     #[error("Query error: {0}")]
     JsonRpcError(#[from] RetryError<JsonRpcError<Method::Error>>),
+    #[error("Transaction outcome is not available at the {0:?} execution status")]
+    MissingTransactionOutcome(near_primitives::views::TxExecutionStatus),
     #[error("Internal error: failed to get response. Please submit a bug ticket")]
     InternalErrorNoResponse,
 }
@@ -176,10 +183,207 @@ pub enum DecimalNumberParsingError {
 pub enum RetryError<E> {
     #[error("No RPC endpoints are defined in the network config")]
     NoRpcEndpoints,
-    #[error("Request failed. Retries exhausted. Last error: {0}")]
-    RetriesExhausted(E),
+    #[error("Request failed. Retries exhausted. {0}")]
+    RetriesExhausted(RetryReport<E>),
+    #[error("Request failed. Deadline of {deadline:?} exceeded. {report}")]
+    DeadlineExceeded {
+        deadline: std::time::Duration,
+        report: RetryReport<E>,
+    },
     #[error("Critical error: {0}")]
     Critical(E),
+    /// The custom [RpcTransport](crate::config::RpcTransport) failed to send the request,
+    /// or the request is not recorded in the replayed cassette, see [NetworkConfig::with_replay](crate::NetworkConfig::with_replay).
+    /// It's not retried, as the transport is shared by all the endpoints.
+    #[error(transparent)]
+    Transport(TransportError),
+}
+
+impl<E> RetryError<E> {
+    /// Returns the error of the last failed attempt, if there was one.
+    pub fn last_error(&self) -> Option<&E> {
+        match self {
+            Self::NoRpcEndpoints | Self::Transport(_) => None,
+            Self::RetriesExhausted(report) | Self::DeadlineExceeded { report, .. } => {
+                report.last_error()
+            }
+            Self::Critical(error) => Some(error),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TransportError {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    /// The request can't be served from the cassette.
+    /// It's reported as [RetryError::Transport], so the replay fails on the first miss.
+    #[error(transparent)]
+    Cassette(#[from] CassetteError),
+    #[error(transparent)]
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CassetteError {
+    #[error("Failed to read or write cassette file: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Failed to parse cassette file: {0}")]
+    ParseError(#[from] serde_json::Error),
+    #[error("Request <{method}> with params {params} is not recorded in the cassette")]
+    UnrecordedRequest { method: String, params: String },
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum BatchError<E> {
+    #[error("Endpoint rejected the batch request: {0}")]
+    Rejected(String),
+    #[error(transparent)]
+    JsonRpcError(#[from] JsonRpcError<E>),
+    #[error(transparent)]
+    TransportError(TransportError),
+}
+
+/// Error of the request sent with the [RpcClient](crate::config::RpcClient).
+#[derive(thiserror::Error, Debug)]
+pub enum RpcCallError<E> {
+    #[error(transparent)]
+    JsonRpcError(#[from] JsonRpcError<E>),
+    /// The custom [RpcTransport](crate::config::RpcTransport) failed, see [RetryError::Transport].
+    /// The HTTP errors are reported as [JsonRpcError]s, the same as by the JSON-RPC client.
+    #[error(transparent)]
+    TransportError(TransportError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum StateProofError {
+    #[error("Failed to fetch the block: {0}")]
+    BlockError(#[from] QueryError<RpcBlockRequest>),
+    #[error("Failed to fetch the protocol config: {0}")]
+    ProtocolConfigError(#[from] QueryError<RpcProtocolConfigRequest>),
+    #[error("Failed to fetch the contract state: {0}")]
+    StateError(#[from] QueryError<RpcQueryRequest>),
+    #[error("Block header doesn't match the block hash {0}")]
+    InvalidBlockHeader(near_primitives::hash::CryptoHash),
+    #[error("Chunk headers don't match the header of the block {0}")]
+    InvalidChunkHeaders(near_primitives::hash::CryptoHash),
+    #[error("Block {block_hash} doesn't include a new chunk for the shard {shard_id}")]
+    MissingChunk {
+        block_hash: near_primitives::hash::CryptoHash,
+        shard_id: near_primitives::types::ShardId,
+    },
+    #[error("Proof node {0} is missing")]
+    MissingProofNode(near_primitives::hash::CryptoHash),
+    #[error("Failed to decode the proof node: {0}")]
+    InvalidProofNode(std::io::Error),
+    #[error("Value of the key {0:?} doesn't match the proof")]
+    UnprovenValue(Vec<u8>),
+    #[error("Value of the key {0:?} is proven, but it's missing in the response")]
+    MissingValue(Vec<u8>),
+}
+
+#[cfg(feature = "blocking")]
+#[derive(thiserror::Error, Debug)]
+pub enum BlockingError<E> {
+    #[error(
+        "Blocking call inside the async runtime would block its thread, use the async API instead"
+    )]
+    InsideRuntime,
+    #[error("Failed to start the runtime: {0}")]
+    RuntimeError(#[from] std::io::Error),
+    #[error(transparent)]
+    Error(E),
+}
+
+/// Reason why a single request attempt failed.
+#[derive(thiserror::Error, Debug)]
+pub enum AttemptError<E> {
+    #[error("{0}")]
+    Error(E),
+    #[error("Timed out after {0:?}")]
+    Timeout(std::time::Duration),
+    #[error("Endpoint failed chain identity verification: {0}")]
+    VerificationFailed(crate::config::EndpointVerification),
+    /// The endpoint asked to back off with `Retry-After` for longer than the retry would wait,
+    /// so the request was sent to the next endpoint.
+    #[error("Endpoint asked to back off for {0:?}")]
+    Throttled(std::time::Duration),
+}
+
+/// Single failed request attempt.
+#[derive(Debug)]
+pub struct FailedAttempt<E> {
+    pub endpoint: url::Url,
+    pub error: AttemptError<E>,
+}
+
+/// Failed attempts of the request in the order they were made, across all the tried endpoints.
+#[derive(Debug)]
+pub struct RetryReport<E> {
+    pub attempts: Vec<FailedAttempt<E>>,
+}
+
+impl<E> RetryReport<E> {
+    /// Endpoints that were tried, in the order of the first attempt.
+    pub fn endpoints(&self) -> Vec<&url::Url> {
+        let mut endpoints = Vec::new();
+        for attempt in &self.attempts {
+            if !endpoints.contains(&&attempt.endpoint) {
+                endpoints.push(&attempt.endpoint);
+            }
+        }
+        endpoints
+    }
+
+    /// Returns the last error returned by the endpoint, skipping the timed out attempts.
+    pub fn last_error(&self) -> Option<&E> {
+        self.attempts
+            .iter()
+            .rev()
+            .find_map(|attempt| match &attempt.error {
+                AttemptError::Error(error) => Some(error),
+                AttemptError::Timeout(_)
+                | AttemptError::VerificationFailed(_)
+                | AttemptError::Throttled(_) => None,
+            })
+    }
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for RetryReport<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Tried {} endpoint(s)", self.endpoints().len())?;
+        for endpoint in self.endpoints() {
+            let mut attempts = self
+                .attempts
+                .iter()
+                .filter(|attempt| &attempt.endpoint == endpoint);
+            let count = attempts.clone().count();
+            if let Some(last) = attempts.next_back() {
+                write!(
+                    f,
+                    "; {endpoint}: {count} attempt(s), last error: {}",
+                    last.error
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum NetworkConfigError {
+    #[error("Failed to read config file: {0}")]
+    ReadError(#[from] std::io::Error),
+    #[error("Failed to parse config file: {0}")]
+    ParseError(#[from] toml::de::Error),
+    #[error("Config file {} set by NEAR_CLI_CONFIG doesn't exist", .0.display())]
+    CliConfigNotFound(std::path::PathBuf),
+    #[error("Network <{0}> is not defined")]
+    UnknownNetwork(String),
+    #[error("NEAR_RPC_URL environment variable is not set")]
+    RpcUrlIsNotDefined,
+    #[error("Invalid value of the {name} environment variable: {reason}")]
+    InvalidEnvVariable { name: &'static str, reason: String },
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -199,6 +403,20 @@ pub enum ExecuteTransactionError {
     CriticalTransactionError(JsonRpcError<RpcTransactionError>),
     #[error(transparent)]
     NonEmptyVecError(#[from] NonEmptyVecError),
+    #[error("Transaction outcome is not available at the {0:?} execution status")]
+    MissingOutcome(near_primitives::views::TxExecutionStatus),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExecutionValueError {
+    #[error("Transaction execution failed: {0}")]
+    Failure(near_primitives::errors::TxExecutionError),
+    #[error("Transaction is not executed yet")]
+    NotExecuted,
+    #[error("Failed to deserialize JSON value: {0}")]
+    DeserializeError(#[from] serde_json::Error),
+    #[error("Failed to deserialize borsh value: {0}")]
+    BorshDeserializeError(#[from] std::io::Error),
 }
 
 #[derive(thiserror::Error, Debug)]
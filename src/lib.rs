@@ -12,6 +12,11 @@ mod transactions;
 // mod fastnear;
 
 mod common;
+#[cfg(test)]
+mod testing;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
 
 pub mod errors;
 pub mod signer;
@@ -20,7 +25,13 @@ pub mod types;
 pub use crate::{
     account::Account,
     chain::Chain,
-    config::{NetworkConfig, RPCEndpoint},
+    config::{
+        AttemptFailedEvent, Cassette, CircuitBreaker, CircuitState, EndpointHealth,
+        EndpointVerification, HttpTransport, InFlightRequests, Interaction, Jitter, NetworkConfig,
+        NetworkRegistry, OperationEvent, OperationKind, RPCEndpoint, RateLimit, RateLimiter,
+        RecordingTransport, ReplayTransport, ResponseCache, RpcCallEvent, RpcErrorKind, RpcHealth,
+        RpcObserver, RpcResponse, RpcTransport,
+    },
     contract::Contract,
     signer::{Signer, SignerTrait},
     stake::Staking,
@@ -28,11 +39,14 @@ pub use crate::{
     tokens::Tokens,
     transactions::Transaction,
     types::{
-        reference::{EpochReference, Reference},
+        reference::{ChunkReference, EpochReference, Reference},
         tokens::{FTBalance, USDT_BALANCE, W_NEAR_BALANCE},
         Data,
     },
 };
 
+#[cfg(feature = "metrics")]
+pub use crate::config::MetricsObserver;
+
 pub use near_account_id::AccountId;
 pub use near_token::NearToken;
@@ -24,6 +24,7 @@ pub struct KeystoreSigner {
 #[async_trait::async_trait]
 impl SignerTrait for KeystoreSigner {
     #[instrument(skip(self, tr), fields(signer_id = %tr.signer_id, receiver_id = %tr.receiver_id))]
+    #[allow(clippy::result_large_err)]
     fn tx_and_secret(
         &self,
         tr: PrepopulateTransaction,
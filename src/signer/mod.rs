@@ -80,6 +80,7 @@ pub trait SignerTrait {
         Ok(SignedTransaction::new(signature, unsigned_transaction))
     }
 
+    #[allow(clippy::result_large_err)]
     fn tx_and_secret(
         &self,
         tr: PrepopulateTransaction,
@@ -87,6 +88,7 @@ pub trait SignerTrait {
         nonce: Nonce,
         block_hash: CryptoHash,
     ) -> Result<(Transaction, SecretKey), SignerError>;
+    #[allow(clippy::result_large_err)]
     fn get_public_key(&self) -> Result<PublicKey, SignerError>;
 }
 
@@ -210,6 +212,7 @@ impl Signer {
     }
 
     #[cfg(feature = "keystore")]
+    #[allow(clippy::result_large_err)]
     pub async fn keystore_search_for_keys(
         account_id: AccountId,
         network: &NetworkConfig,
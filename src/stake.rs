@@ -8,9 +8,9 @@ use near_token::NearToken;
 use crate::{
     common::{
         query::{
-            CallResultHandler, MultiQueryBuilder, MultiQueryHandler, PostprocessHandler,
-            QueryBuilder, QueryCreator, RpcValidatorHandler, SimpleQuery, SimpleValidatorRpc,
-            ValidatorQueryBuilder, ViewStateHandler,
+            CallResultHandler, CollectionHandler, MultiQueryBuilder, MultiQueryHandler,
+            PostprocessHandler, QueryBuilder, QueryCreator, RpcValidatorHandler, SimpleQuery,
+            SimpleValidatorRpc, ValidatorQueryBuilder, ViewStateHandler,
         },
         utils::is_critical_query_error,
     },
@@ -18,6 +18,7 @@ use crate::{
     errors::{BuilderError, QueryCreationError, QueryError},
     transactions::ConstructTransaction,
     types::{
+        collections::UnorderedSet,
         stake::{RewardFeeFraction, StakingPoolInfo, UserStakeBalance},
         Data,
     },
@@ -253,6 +254,9 @@ impl Delegation {
 pub struct Staking {}
 
 impl Staking {
+    /// Returns the pools of the staking pool factory, skipping the items that can't be decoded.
+    ///
+    /// See [Staking::active_staking_pools_strict] to fail on them instead.
     pub fn active_staking_pools(
     ) -> QueryBuilder<PostprocessHandler<std::collections::BTreeSet<AccountId>, ViewStateHandler>>
     {
@@ -270,6 +274,24 @@ impl Staking {
         )
     }
 
+    /// Same as [Staking::active_staking_pools], but fails on the first item that can't be decoded.
+    pub fn active_staking_pools_strict() -> QueryBuilder<
+        PostprocessHandler<
+            std::collections::BTreeSet<AccountId>,
+            CollectionHandler<UnorderedSet<AccountId>>,
+        >,
+    > {
+        QueryBuilder::new(
+            ActiveStakingPoolQuery,
+            BlockReference::latest(),
+            PostprocessHandler::new(
+                // The pool factory keeps the pools in `UnorderedSet` with the `s` prefix
+                CollectionHandler(UnorderedSet::new(b"s")),
+                |query_result| query_result.data.into_iter().collect(),
+            ),
+        )
+    }
+
     pub fn epoch_validators_info() -> ValidatorQueryBuilder<RpcValidatorHandler> {
         ValidatorQueryBuilder::new(
             SimpleValidatorRpc,
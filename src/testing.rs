@@ -0,0 +1,233 @@
+//! Helpers shared by the unit tests.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde_json::json;
+
+use crate::{
+    config::{NetworkConfig, RPCEndpoint, RpcResponse, RpcTransport},
+    errors::TransportError,
+};
+
+pub const HASH: &str = "11111111111111111111111111111111";
+
+type Respond = dyn Fn(&RPCEndpoint, &serde_json::Value) -> RpcResponse + Send + Sync;
+type Latency = dyn Fn(usize) -> Duration + Send + Sync;
+
+/// Transport that answers the requests with the given function and remembers them.
+pub struct ScriptedTransport {
+    respond: Box<Respond>,
+    latency: Box<Latency>,
+    requests: Mutex<Vec<(url::Url, serde_json::Value)>>,
+}
+
+impl ScriptedTransport {
+    pub fn new(
+        respond: impl Fn(&RPCEndpoint, &serde_json::Value) -> RpcResponse + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            respond: Box::new(respond),
+            latency: Box::new(|_| Duration::ZERO),
+            requests: Mutex::default(),
+        }
+    }
+
+    /// Answers the requests with the given responses in order.
+    pub fn responses(responses: impl IntoIterator<Item = RpcResponse>) -> Self {
+        let responses = Mutex::new(responses.into_iter().collect::<VecDeque<_>>());
+        Self::new(move |_, request| {
+            responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or_else(|| panic!("No response is scripted for {request}"))
+        })
+    }
+
+    /// Delays the response to the request with the given index.
+    pub fn with_latency(
+        mut self,
+        latency: impl Fn(usize) -> Duration + Send + Sync + 'static,
+    ) -> Self {
+        self.latency = Box::new(latency);
+        self
+    }
+
+    /// Requests received by the transport, in order.
+    pub fn requests(&self) -> Vec<serde_json::Value> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, request)| request.clone())
+            .collect()
+    }
+
+    /// Number of the requests sent to the endpoint.
+    pub fn requests_to(&self, url: &str) -> usize {
+        let url: url::Url = url.parse().unwrap();
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(endpoint, _)| *endpoint == url)
+            .count()
+    }
+}
+
+impl Default for ScriptedTransport {
+    fn default() -> Self {
+        Self::new(|_, request| panic!("Unexpected request {request}"))
+    }
+}
+
+impl std::fmt::Debug for ScriptedTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScriptedTransport")
+            .field("requests", &self.requests)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl RpcTransport for ScriptedTransport {
+    async fn send(
+        &self,
+        endpoint: &RPCEndpoint,
+        request: &serde_json::Value,
+    ) -> Result<RpcResponse, TransportError> {
+        let index = {
+            let mut requests = self.requests.lock().unwrap();
+            requests.push((endpoint.url.clone(), request.clone()));
+            requests.len() - 1
+        };
+        let latency = (self.latency)(index);
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        Ok((self.respond)(endpoint, request))
+    }
+}
+
+/// Endpoint with 3 retries and short sleeps between them.
+pub fn endpoint(url: &str) -> RPCEndpoint {
+    RPCEndpoint::new(url.parse().unwrap())
+        .with_retries(3)
+        .with_initial_sleep(Duration::from_millis(1))
+}
+
+/// Testnet config with the single [endpoint] that sends the requests to the transport.
+pub fn network(transport: ScriptedTransport) -> (NetworkConfig, Arc<ScriptedTransport>) {
+    network_with_endpoints(&["https://rpc.testnet.near.org"], transport)
+}
+
+/// Testnet config with the given [endpoint]s that sends the requests to the transport.
+pub fn network_with_endpoints(
+    urls: &[&str],
+    transport: ScriptedTransport,
+) -> (NetworkConfig, Arc<ScriptedTransport>) {
+    let transport = Arc::new(transport);
+    let mut network = NetworkConfig::testnet();
+    network.rpc_endpoints = urls.iter().map(|url| endpoint(url)).collect();
+    network.transport = transport.clone();
+    (network, transport)
+}
+
+/// Successful JSON-RPC response with the given result.
+pub fn rpc_result(result: serde_json::Value) -> RpcResponse {
+    RpcResponse::ok(json!({"jsonrpc": "2.0", "id": "dontcare", "result": result}).to_string())
+}
+
+/// JSON-RPC response with the given error.
+pub fn rpc_error(error: impl serde::Serialize) -> RpcResponse {
+    RpcResponse::ok(json!({"jsonrpc": "2.0", "id": "dontcare", "error": error}).to_string())
+}
+
+/// Response to the function call that returned the JSON value at the given block height.
+pub fn call_result(value: impl serde::Serialize, block_height: u64) -> RpcResponse {
+    rpc_result(json!({
+        "result": serde_json::to_vec(&value).unwrap(),
+        "logs": [],
+        "block_height": block_height,
+        "block_hash": near_primitives::hash::CryptoHash::default(),
+    }))
+}
+
+/// `HANDLER_ERROR` response with the given cause.
+pub fn handler_error(name: &str, info: serde_json::Value) -> RpcResponse {
+    rpc_error(json!({
+        "name": "HANDLER_ERROR",
+        "cause": {"name": name, "info": info},
+        "code": -32000,
+        "message": "Server error",
+        "data": name,
+    }))
+}
+
+/// Response to the `status` request of the node serving the given chain.
+pub fn status(chain_id: &str) -> RpcResponse {
+    rpc_result(json!({
+        "version": {"version": "2.3.0", "build": "test"},
+        "chain_id": chain_id,
+        "protocol_version": 72,
+        "latest_protocol_version": 72,
+        "validators": [],
+        "sync_info": {
+            "latest_block_hash": HASH,
+            "latest_block_height": 1,
+            "latest_state_root": HASH,
+            "latest_block_time": "2024-01-01T00:00:00Z",
+            "syncing": false,
+            "earliest_block_hash": null,
+            "earliest_block_height": null,
+            "earliest_block_time": null,
+            "epoch_id": null,
+            "epoch_start_height": null,
+        },
+        "validator_account_id": null,
+        "validator_public_key": null,
+        "node_public_key": format!("ed25519:{HASH}"),
+        "node_key": null,
+        "uptime_sec": 1,
+        "genesis_hash": HASH,
+    }))
+}
+
+/// Execution outcome of the receipt or transaction.
+pub fn outcome(executor_id: &str, status: serde_json::Value, logs: &[&str]) -> serde_json::Value {
+    json!({
+        "proof": [],
+        "block_hash": HASH,
+        "id": HASH,
+        "outcome": {
+            "logs": logs,
+            "receipt_ids": [],
+            "gas_burnt": 100,
+            "tokens_burnt": "1000",
+            "executor_id": executor_id,
+            "status": status,
+        },
+    })
+}
+
+/// Final execution outcome of the successful transaction with the given receipt outcomes.
+pub fn final_outcome(receipts: Vec<serde_json::Value>) -> serde_json::Value {
+    json!({
+        "status": {"SuccessValue": "eyJhIjoxfQ=="},
+        "transaction": {
+            "signer_id": "alice.near",
+            "public_key": format!("ed25519:{HASH}"),
+            "nonce": 1,
+            "receiver_id": "contract.near",
+            "actions": [],
+            "signature": format!("ed25519:{HASH}{HASH}"),
+            "hash": HASH,
+        },
+        "transaction_outcome": outcome("alice.near", json!({"SuccessReceiptId": HASH}), &[]),
+        "receipts_outcome": receipts,
+    })
+}
@@ -214,6 +214,7 @@ pub struct FTTransactionable {
 }
 
 impl FTTransactionable {
+    #[allow(clippy::result_large_err)]
     pub async fn check_decimals(
         &self,
         network: &NetworkConfig,
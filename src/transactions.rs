@@ -1,13 +1,20 @@
 use std::sync::Arc;
 
-use near_primitives::{action::Action, types::AccountId};
+use near_primitives::{action::Action, types::AccountId, views::TxExecutionStatus};
 
 use crate::{
-    common::send::{ExecuteSignedTransaction, Transactionable},
+    common::{
+        query::{
+            RpcTransactionStatusHandler, RpcTransactionStatusWithReceiptsHandler,
+            SimpleTransactionStatusRpc, TransactionStatusQueryBuilder,
+            TransactionStatusWithReceiptsQueryBuilder,
+        },
+        send::{ExecuteSignedTransaction, Transactionable},
+    },
     config::NetworkConfig,
     errors::{SignerError, ValidationError},
     signer::Signer,
-    types::transactions::PrepopulateTransaction,
+    types::{transactions::PrepopulateTransaction, CryptoHash},
 };
 
 #[derive(Clone, Debug)]
@@ -75,6 +82,7 @@ impl Transaction {
         ConstructTransaction::new(signer_id, receiver_id)
     }
 
+    #[allow(clippy::result_large_err)]
     pub async fn sign_transaction(
         unsigned_tx: near_primitives::transaction::Transaction,
         signer: Arc<Signer>,
@@ -92,4 +100,39 @@ impl Transaction {
         .presign_offline(public_key, block_hash.into(), nonce)
         .await
     }
+
+    /// Looks up the transaction outcome by its hash and sender, e.g. after the broadcast has timed out.
+    ///
+    /// By default, the node waits until the transaction is [TxExecutionStatus::ExecutedOptimistic],
+    /// use `wait_until` to change it. The statuses below
+    /// [TxExecutionStatus::ExecutedOptimistic] have no outcome yet, so the returned
+    /// [TransactionStatus::outcome](crate::types::transactions::TransactionStatus::outcome) is `None`.
+    pub fn status(
+        tx_hash: CryptoHash,
+        sender_id: AccountId,
+    ) -> TransactionStatusQueryBuilder<RpcTransactionStatusHandler> {
+        TransactionStatusQueryBuilder::new(
+            SimpleTransactionStatusRpc {
+                tx_hash: tx_hash.into(),
+                sender_id,
+            },
+            TxExecutionStatus::ExecutedOptimistic,
+            RpcTransactionStatusHandler,
+        )
+    }
+
+    /// The same as [Transaction::status], but the outcome includes the receipts.
+    pub fn status_with_receipts(
+        tx_hash: CryptoHash,
+        sender_id: AccountId,
+    ) -> TransactionStatusWithReceiptsQueryBuilder<RpcTransactionStatusWithReceiptsHandler> {
+        TransactionStatusWithReceiptsQueryBuilder::new(
+            SimpleTransactionStatusRpc {
+                tx_hash: tx_hash.into(),
+                sender_id,
+            },
+            TxExecutionStatus::ExecutedOptimistic,
+            RpcTransactionStatusWithReceiptsHandler,
+        )
+    }
 }
@@ -0,0 +1,324 @@
+//! Storage layouts of the near-sdk collections, to decode them from the contract state.
+//!
+//! The layouts match the `near_sdk::collections` module. The collection prefix is the one passed
+//! to the collection constructor, e.g. `b"m"` or the borsh-serialized storage key enum.
+//!
+//! Use them with [Contract::view_collection](crate::Contract::view_collection), or decode the state
+//! downloaded with [Contract::download_state](crate::Contract::download_state) with [StorageLayout::decode].
+
+use std::{io, marker::PhantomData};
+
+use borsh::BorshDeserialize;
+use near_primitives::views::StateItem;
+
+/// Key of the contract struct in the near-sdk contract state.
+pub const STATE_KEY: &[u8] = b"STATE";
+
+/// Storage layout of the collection.
+pub trait StorageLayout {
+    type Output;
+
+    /// Prefix of all the keys that belong to the collection.
+    fn prefix(&self) -> &[u8];
+
+    /// Decodes the collection from the state items. The items outside of the [StorageLayout::prefix] are ignored.
+    fn decode(&self, items: &[StateItem]) -> io::Result<Self::Output>;
+}
+
+/// Iterates over the items under the prefix, with the prefix stripped from the keys.
+fn items_with_prefix<'a>(
+    items: &'a [StateItem],
+    prefix: &'a [u8],
+) -> impl Iterator<Item = (&'a [u8], &'a [u8])> {
+    items.iter().filter_map(move |item| {
+        item.key
+            .strip_prefix(prefix)
+            .map(|key| (key, item.value.as_slice()))
+    })
+}
+
+fn with_suffix(prefix: &[u8], suffix: u8) -> Vec<u8> {
+    let mut prefix = prefix.to_vec();
+    prefix.push(suffix);
+    prefix
+}
+
+/// The contract struct stored under the [STATE_KEY].
+#[derive(Debug, Clone)]
+pub struct ContractState<T>(PhantomData<fn() -> T>);
+
+impl<T> ContractState<T> {
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for ContractState<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: BorshDeserialize> StorageLayout for ContractState<T> {
+    type Output = T;
+
+    fn prefix(&self) -> &[u8] {
+        STATE_KEY
+    }
+
+    fn decode(&self, items: &[StateItem]) -> io::Result<T> {
+        let (_, value) = items_with_prefix(items, STATE_KEY)
+            .find(|(key, _)| key.is_empty())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "STATE key is not found"))?;
+        borsh::from_slice(value)
+    }
+}
+
+/// `LookupMap<K, V>`: the values are stored under the prefix followed by the borsh-serialized key.
+#[derive(Debug, Clone)]
+pub struct LookupMap<K, V> {
+    prefix: Vec<u8>,
+    _phantom: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> LookupMap<K, V> {
+    pub fn new(prefix: impl Into<Vec<u8>>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<K: BorshDeserialize, V: BorshDeserialize> StorageLayout for LookupMap<K, V> {
+    type Output = Vec<(K, V)>;
+
+    fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    fn decode(&self, items: &[StateItem]) -> io::Result<Self::Output> {
+        items_with_prefix(items, &self.prefix)
+            .map(|(key, value)| Ok((borsh::from_slice(key)?, borsh::from_slice(value)?)))
+            .collect()
+    }
+}
+
+/// `LookupSet<T>`: the elements are stored as the keys under the prefix, with empty values.
+#[derive(Debug, Clone)]
+pub struct LookupSet<T> {
+    prefix: Vec<u8>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> LookupSet<T> {
+    pub fn new(prefix: impl Into<Vec<u8>>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: BorshDeserialize> StorageLayout for LookupSet<T> {
+    type Output = Vec<T>;
+
+    fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    fn decode(&self, items: &[StateItem]) -> io::Result<Self::Output> {
+        items_with_prefix(items, &self.prefix)
+            .map(|(key, _)| borsh::from_slice(key))
+            .collect()
+    }
+}
+
+/// `Vector<T>`: the elements are stored under the prefix followed by the `u64` index.
+#[derive(Debug, Clone)]
+pub struct Vector<T> {
+    prefix: Vec<u8>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Vector<T> {
+    pub fn new(prefix: impl Into<Vec<u8>>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: BorshDeserialize> StorageLayout for Vector<T> {
+    type Output = Vec<T>;
+
+    fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    fn decode(&self, items: &[StateItem]) -> io::Result<Self::Output> {
+        let mut elements = items_with_prefix(items, &self.prefix)
+            .map(|(index, value)| Ok((borsh::from_slice::<u64>(index)?, borsh::from_slice(value)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+        elements.sort_by_key(|(index, _)| *index);
+        Ok(elements.into_iter().map(|(_, element)| element).collect())
+    }
+}
+
+/// `UnorderedMap<K, V>`: the keys and values are stored in two [Vector]s
+/// with the `k` and `v` suffixes, and the key index in the [LookupMap] with the `i` suffix.
+#[derive(Debug, Clone)]
+pub struct UnorderedMap<K, V> {
+    prefix: Vec<u8>,
+    _phantom: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> UnorderedMap<K, V> {
+    pub fn new(prefix: impl Into<Vec<u8>>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<K: BorshDeserialize, V: BorshDeserialize> StorageLayout for UnorderedMap<K, V> {
+    type Output = Vec<(K, V)>;
+
+    fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    fn decode(&self, items: &[StateItem]) -> io::Result<Self::Output> {
+        let keys = Vector::<K>::new(with_suffix(&self.prefix, b'k')).decode(items)?;
+        let values = Vector::<V>::new(with_suffix(&self.prefix, b'v')).decode(items)?;
+        if keys.len() != values.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "UnorderedMap has {} keys but {} values",
+                    keys.len(),
+                    values.len()
+                ),
+            ));
+        }
+        Ok(keys.into_iter().zip(values).collect())
+    }
+}
+
+/// `UnorderedSet<T>`: the elements are stored in the [Vector] with the `e` suffix,
+/// and the element index in the [LookupMap] with the `i` suffix.
+#[derive(Debug, Clone)]
+pub struct UnorderedSet<T> {
+    prefix: Vec<u8>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> UnorderedSet<T> {
+    pub fn new(prefix: impl Into<Vec<u8>>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: BorshDeserialize> StorageLayout for UnorderedSet<T> {
+    type Output = Vec<T>;
+
+    fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    fn decode(&self, items: &[StateItem]) -> io::Result<Self::Output> {
+        Vector::new(with_suffix(&self.prefix, b'e')).decode(items)
+    }
+}
+
+/// `TreeMap<K, V>`: the values are stored in the [LookupMap] with the `v` suffix,
+/// and the tree nodes in the [Vector] with the `n` suffix. The entries are sorted by key.
+#[derive(Debug, Clone)]
+pub struct TreeMap<K, V> {
+    prefix: Vec<u8>,
+    _phantom: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> TreeMap<K, V> {
+    pub fn new(prefix: impl Into<Vec<u8>>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<K: BorshDeserialize + Ord, V: BorshDeserialize> StorageLayout for TreeMap<K, V> {
+    type Output = Vec<(K, V)>;
+
+    fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    fn decode(&self, items: &[StateItem]) -> io::Result<Self::Output> {
+        let mut entries = LookupMap::<K, V>::new(with_suffix(&self.prefix, b'v')).decode(items)?;
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(key: impl AsRef<[u8]>, value: impl borsh::BorshSerialize) -> StateItem {
+        StateItem {
+            key: key.as_ref().to_vec().into(),
+            value: borsh::to_vec(&value).unwrap().into(),
+        }
+    }
+
+    fn key(prefix: &[u8], key: impl borsh::BorshSerialize) -> Vec<u8> {
+        [prefix, &borsh::to_vec(&key).unwrap()].concat()
+    }
+
+    #[test]
+    fn decodes_near_sdk_collections() {
+        let items = vec![
+            item(STATE_KEY, (7u32, String::from("owner"))),
+            // UnorderedMap<String, u128> with prefix "m"
+            item(key(b"mi", "b"), 1u64),
+            item(key(b"mi", "a"), 0u64),
+            item(key(b"mk", 1u64), "b"),
+            item(key(b"mk", 0u64), "a"),
+            item(key(b"mv", 0u64), 10u128),
+            item(key(b"mv", 1u64), 20u128),
+            // Vector<u8> with prefix "v"
+            item(key(b"v", 1u64), 2u8),
+            item(key(b"v", 0u64), 1u8),
+            // TreeMap<u32, bool> with prefix "t"
+            item(key(b"tv", 3u32), true),
+            item(key(b"tv", 1u32), false),
+            item(key(b"tn", 0u64), [0u8; 4]),
+        ];
+
+        assert_eq!(
+            ContractState::<(u32, String)>::new()
+                .decode(&items)
+                .unwrap(),
+            (7, String::from("owner"))
+        );
+        assert_eq!(
+            UnorderedMap::<String, u128>::new(b"m")
+                .decode(&items)
+                .unwrap(),
+            vec![(String::from("a"), 10), (String::from("b"), 20)]
+        );
+        assert_eq!(Vector::<u8>::new(b"v").decode(&items).unwrap(), vec![1, 2]);
+        assert_eq!(
+            TreeMap::<u32, bool>::new(b"t").decode(&items).unwrap(),
+            vec![(1, false), (3, true)]
+        );
+        assert!(LookupMap::<String, u64>::new(b"m").decode(&items).is_err());
+    }
+}
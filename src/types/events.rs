@@ -0,0 +1,211 @@
+//! NEP-297 events, that the contracts emit as the `EVENT_JSON:` logs.
+//!
+//! The event data of the `nep141` and `nep171` standards mirrors the `near_contract_standards`
+//! event types, which only support serialization.
+//!
+//! Use [TransactionOutcome::events](crate::types::outcome::TransactionOutcome::events) to get the events
+//! of the transaction, or [EventLog::parse] for the single log.
+
+use near_contract_standards::non_fungible_token::TokenId;
+use near_primitives::types::AccountId;
+use near_sdk::json_types::U128;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::types::CryptoHash;
+
+pub const EVENT_LOG_PREFIX: &str = "EVENT_JSON:";
+
+/// Event as it's logged by the contract.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventLog {
+    pub standard: String,
+    pub version: String,
+    pub event: String,
+    /// The data is optional in NEP-297, and it's `null` if the event has none.
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+impl EventLog {
+    /// Parses the event log. Returns `None` if the log is not an event.
+    pub fn parse(log: &str) -> Option<Result<Self, serde_json::Error>> {
+        log.strip_prefix(EVENT_LOG_PREFIX).map(serde_json::from_str)
+    }
+}
+
+/// Event with the receipt it was emitted by, and the contract that executed the receipt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event<T> {
+    pub standard: String,
+    pub version: String,
+    pub event: String,
+    pub data: T,
+    pub receipt_id: CryptoHash,
+    pub contract_id: AccountId,
+}
+
+impl Event<serde_json::Value> {
+    pub(crate) fn new(log: EventLog, receipt_id: CryptoHash, contract_id: AccountId) -> Self {
+        Self {
+            standard: log.standard,
+            version: log.version,
+            event: log.event,
+            data: log.data,
+            receipt_id,
+            contract_id,
+        }
+    }
+
+    /// Decodes the event data of the custom standard.
+    pub fn decode<T: DeserializeOwned>(mut self) -> Result<Event<T>, serde_json::Error> {
+        let data = serde_json::from_value(self.data.take())?;
+        Ok(self.with_data(data))
+    }
+
+    /// Decodes the event of the standard, that is tagged with the event name, like [Nep141Event].
+    pub(crate) fn decode_tagged<T: DeserializeOwned>(
+        mut self,
+    ) -> Result<Event<T>, serde_json::Error> {
+        let tagged = serde_json::json!({ "event": self.event, "data": self.data.take() });
+        let data = serde_json::from_value(tagged)?;
+        Ok(self.with_data(data))
+    }
+
+    fn with_data<T>(self, data: T) -> Event<T> {
+        Event {
+            standard: self.standard,
+            version: self.version,
+            event: self.event,
+            data,
+            receipt_id: self.receipt_id,
+            contract_id: self.contract_id,
+        }
+    }
+}
+
+/// Events of the `nep141` fungible token standard.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+pub enum Nep141Event {
+    FtMint(Vec<FtMint>),
+    FtTransfer(Vec<FtTransfer>),
+    FtBurn(Vec<FtBurn>),
+}
+
+impl Nep141Event {
+    pub const STANDARD: &'static str = "nep141";
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FtMint {
+    pub owner_id: AccountId,
+    pub amount: U128,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FtTransfer {
+    pub old_owner_id: AccountId,
+    pub new_owner_id: AccountId,
+    pub amount: U128,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FtBurn {
+    pub owner_id: AccountId,
+    pub amount: U128,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/// Events of the `nep171` non-fungible token standard.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+pub enum Nep171Event {
+    NftMint(Vec<NftMint>),
+    NftTransfer(Vec<NftTransfer>),
+    NftBurn(Vec<NftBurn>),
+}
+
+impl Nep171Event {
+    pub const STANDARD: &'static str = "nep171";
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftMint {
+    pub owner_id: AccountId,
+    pub token_ids: Vec<TokenId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftTransfer {
+    pub old_owner_id: AccountId,
+    pub new_owner_id: AccountId,
+    pub token_ids: Vec<TokenId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorized_id: Option<AccountId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NftBurn {
+    pub owner_id: AccountId,
+    pub token_ids: Vec<TokenId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorized_id: Option<AccountId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(log: &str) -> Event<serde_json::Value> {
+        let log = EventLog::parse(log).unwrap().unwrap();
+        Event::new(log, CryptoHash::default(), "nft.near".parse().unwrap())
+    }
+
+    #[test]
+    fn parses_event_logs() {
+        assert!(EventLog::parse("Transfer 10 from alice.near to bob.near").is_none());
+        assert!(EventLog::parse("EVENT_JSON:not a json").unwrap().is_err());
+
+        let log =
+            EventLog::parse(r#"EVENT_JSON:{"standard":"game","version":"1.0.0","event":"start"}"#)
+                .unwrap()
+                .unwrap();
+        assert_eq!(log.event, "start");
+        assert!(log.data.is_null());
+    }
+
+    #[test]
+    fn decodes_tagged_events() {
+        let mint = event(
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[{"owner_id":"alice.near","token_ids":["1","2"]}]}"#,
+        );
+        let mint = mint.decode_tagged::<Nep171Event>().unwrap();
+        assert_eq!(mint.contract_id, "nft.near");
+        assert_eq!(
+            mint.data,
+            Nep171Event::NftMint(vec![NftMint {
+                owner_id: "alice.near".parse().unwrap(),
+                token_ids: vec!["1".to_string(), "2".to_string()],
+                memo: None,
+            }])
+        );
+
+        let unknown = event(
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_lend","data":[]}"#,
+        );
+        assert!(unknown.decode_tagged::<Nep171Event>().is_err());
+    }
+}
@@ -5,7 +5,10 @@ use reqwest::header::InvalidHeaderValue;
 
 use crate::errors::CryptoHashError;
 
+pub mod collections;
 pub mod contract;
+pub mod events;
+pub mod outcome;
 pub mod reference;
 pub mod stake;
 pub mod storage;
@@ -26,6 +29,23 @@ pub struct Data<T> {
     pub block_hash: CryptoHash,
 }
 
+/// The data that is verified with the proof against the state root, so it doesn't rely on the RPC node being honest.
+#[derive(
+    Debug,
+    Clone,
+    serde::Serialize,
+    serde::Deserialize,
+    borsh::BorshDeserialize,
+    borsh::BorshSerialize,
+)]
+pub struct Verified<T> {
+    pub data: T,
+    /// The state root that the data was verified against.
+    pub state_root: CryptoHash,
+    /// The block that commits to the state root in its chunk header.
+    pub verified_at: CryptoHash,
+}
+
 #[derive(Eq, Hash, Clone, Debug, PartialEq)]
 pub struct ApiKey(pub near_jsonrpc_client::auth::ApiKey);
 
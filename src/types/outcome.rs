@@ -0,0 +1,338 @@
+//! Execution outcome of the transaction, with the helpers to inspect the result of all its receipts.
+
+use borsh::BorshDeserialize;
+use near_gas::NearGas;
+use near_primitives::{
+    errors::TxExecutionError,
+    types::AccountId,
+    views::{
+        ExecutionOutcomeWithIdView, ExecutionStatusView, FinalExecutionOutcomeView,
+        FinalExecutionStatus,
+    },
+};
+use near_token::NearToken;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    errors::ExecutionValueError,
+    types::{
+        events::{Event, EventLog, Nep141Event, Nep171Event},
+        CryptoHash,
+    },
+};
+
+/// Failed receipt of the transaction, and the account it was executed on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptFailure {
+    pub receipt_id: CryptoHash,
+    pub executor_id: AccountId,
+    pub error: TxExecutionError,
+}
+
+/// Outcome of the executed transaction.
+///
+/// The transaction status is the result of its final receipt, so the transaction can succeed
+/// even if some of its receipts have failed, e.g. a callback. See [TransactionOutcome::receipt_failures].
+#[derive(Debug, Clone)]
+pub struct TransactionOutcome {
+    view: FinalExecutionOutcomeView,
+}
+
+impl TransactionOutcome {
+    pub fn transaction_hash(&self) -> CryptoHash {
+        self.view.transaction_outcome.id.into()
+    }
+
+    pub const fn view(&self) -> &FinalExecutionOutcomeView {
+        &self.view
+    }
+
+    pub fn into_view(self) -> FinalExecutionOutcomeView {
+        self.view
+    }
+
+    pub const fn is_success(&self) -> bool {
+        matches!(self.view.status, FinalExecutionStatus::SuccessValue(_))
+    }
+
+    pub const fn is_failure(&self) -> bool {
+        matches!(self.view.status, FinalExecutionStatus::Failure(_))
+    }
+
+    /// The error the transaction has failed with.
+    pub const fn failure(&self) -> Option<&TxExecutionError> {
+        match &self.view.status {
+            FinalExecutionStatus::Failure(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Returns the error if the transaction has failed.
+    pub fn into_result(self) -> Result<Self, TxExecutionError> {
+        match self.view.status {
+            FinalExecutionStatus::Failure(error) => Err(error),
+            _ => Ok(self),
+        }
+    }
+
+    /// Panics if the transaction has failed.
+    ///
+    /// Only the final status is checked, so the failed receipts whose failure
+    /// didn't reach the final status (e.g. failed cross-contract callbacks) are ignored.
+    /// Use [TransactionOutcome::assert_all_receipts_success] to check them too.
+    #[track_caller]
+    pub fn assert_success(&self) {
+        assert!(self.is_success(), "error: {:?}", self.view.status);
+    }
+
+    /// Panics if the transaction or any of its receipts has failed.
+    #[track_caller]
+    pub fn assert_all_receipts_success(&self) {
+        self.assert_success();
+        if let Some(failure) = self.receipt_failures().into_iter().next() {
+            panic!(
+                "receipt {} failed on {}: {}",
+                failure.receipt_id, failure.executor_id, failure.error
+            );
+        }
+    }
+
+    /// The transaction outcome followed by the outcomes of all its receipts.
+    fn outcomes(&self) -> impl Iterator<Item = &ExecutionOutcomeWithIdView> {
+        std::iter::once(&self.view.transaction_outcome).chain(&self.view.receipts_outcome)
+    }
+
+    /// Gas burnt by the transaction and all its receipts.
+    pub fn total_gas_burnt(&self) -> NearGas {
+        NearGas::from_gas(
+            self.outcomes()
+                .map(|outcome| outcome.outcome.gas_burnt)
+                .sum(),
+        )
+    }
+
+    /// Tokens burnt for the gas by the transaction and all its receipts.
+    pub fn total_tokens_burnt(&self) -> NearToken {
+        NearToken::from_yoctonear(
+            self.outcomes()
+                .map(|outcome| outcome.outcome.tokens_burnt)
+                .sum(),
+        )
+    }
+
+    /// Logs of all the receipts, in the order of the receipt outcomes.
+    pub fn logs(&self) -> Vec<&str> {
+        self.outcomes()
+            .flat_map(|outcome| outcome.outcome.logs.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// All the failed receipts, including the ones that didn't fail the transaction.
+    pub fn receipt_failures(&self) -> Vec<ReceiptFailure> {
+        self.view
+            .receipts_outcome
+            .iter()
+            .filter_map(|receipt| match &receipt.outcome.status {
+                ExecutionStatusView::Failure(error) => Some(ReceiptFailure {
+                    receipt_id: receipt.id.into(),
+                    executor_id: receipt.outcome.executor_id.clone(),
+                    error: error.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Raw value returned by the final receipt.
+    pub fn raw_value(&self) -> Result<&[u8], ExecutionValueError> {
+        match &self.view.status {
+            FinalExecutionStatus::SuccessValue(value) => Ok(value),
+            FinalExecutionStatus::Failure(error) => {
+                Err(ExecutionValueError::Failure(error.clone()))
+            }
+            FinalExecutionStatus::NotStarted | FinalExecutionStatus::Started => {
+                Err(ExecutionValueError::NotExecuted)
+            }
+        }
+    }
+
+    /// Deserializes the JSON value returned by the final receipt.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, ExecutionValueError> {
+        Ok(serde_json::from_slice(self.raw_value()?)?)
+    }
+
+    /// Deserializes the borsh value returned by the final receipt.
+    pub fn borsh<T: BorshDeserialize>(&self) -> Result<T, ExecutionValueError> {
+        Ok(borsh::from_slice(self.raw_value()?)?)
+    }
+
+    /// NEP-297 events emitted by the receipts, in the order of the receipt outcomes.
+    ///
+    /// The logs with the event prefix that are not valid events are skipped, the same as the indexers do.
+    pub fn events(&self) -> Vec<Event<serde_json::Value>> {
+        self.view
+            .receipts_outcome
+            .iter()
+            .flat_map(|receipt| {
+                receipt
+                    .outcome
+                    .logs
+                    .iter()
+                    .filter_map(|log| EventLog::parse(log)?.ok())
+                    .map(|log| {
+                        Event::new(log, receipt.id.into(), receipt.outcome.executor_id.clone())
+                    })
+            })
+            .collect()
+    }
+
+    /// Events of the custom standard with the event name, with the data decoded.
+    pub fn events_of<T: DeserializeOwned>(
+        &self,
+        standard: &str,
+        event: &str,
+    ) -> Result<Vec<Event<T>>, serde_json::Error> {
+        self.events()
+            .into_iter()
+            .filter(|log| log.standard == standard && log.event == event)
+            .map(Event::decode)
+            .collect()
+    }
+
+    /// Events of the `nep141` fungible token standard.
+    ///
+    /// The events that don't match the standard are skipped, like the malformed logs in [TransactionOutcome::events].
+    pub fn ft_events(&self) -> Vec<Event<Nep141Event>> {
+        self.standard_events(Nep141Event::STANDARD)
+    }
+
+    /// Events of the `nep171` non-fungible token standard.
+    ///
+    /// The events that don't match the standard are skipped, like the malformed logs in [TransactionOutcome::events].
+    pub fn nft_events(&self) -> Vec<Event<Nep171Event>> {
+        self.standard_events(Nep171Event::STANDARD)
+    }
+
+    fn standard_events<T: DeserializeOwned>(&self, standard: &str) -> Vec<Event<T>> {
+        self.events()
+            .into_iter()
+            .filter(|log| log.standard == standard)
+            .filter_map(|log| log.decode_tagged().ok())
+            .collect()
+    }
+}
+
+impl From<FinalExecutionOutcomeView> for TransactionOutcome {
+    fn from(view: FinalExecutionOutcomeView) -> Self {
+        Self { view }
+    }
+}
+
+impl From<TransactionOutcome> for FinalExecutionOutcomeView {
+    fn from(outcome: TransactionOutcome) -> Self {
+        outcome.view
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::testing::{final_outcome, outcome, HASH};
+
+    use super::*;
+
+    fn transaction_outcome(receipts: Vec<serde_json::Value>) -> TransactionOutcome {
+        serde_json::from_value::<FinalExecutionOutcomeView>(final_outcome(receipts))
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn summarizes_receipts() {
+        let failure = json!({"Failure": {"ActionError": {
+            "index": 0,
+            "kind": {"FunctionCallError": {"ExecutionError": "Smart contract panicked"}},
+        }}});
+        let outcome = transaction_outcome(vec![
+            outcome(
+                "contract.near",
+                json!({"SuccessReceiptId": HASH}),
+                &["first"],
+            ),
+            outcome("other.near", failure, &["second"]),
+            outcome(
+                "contract.near",
+                json!({"SuccessValue": "eyJhIjoxfQ=="}),
+                &["third"],
+            ),
+        ]);
+
+        assert!(outcome.is_success());
+        assert_eq!(outcome.total_gas_burnt(), NearGas::from_gas(400));
+        assert_eq!(
+            outcome.total_tokens_burnt(),
+            NearToken::from_yoctonear(4000)
+        );
+        assert_eq!(outcome.logs(), vec!["first", "second", "third"]);
+        assert_eq!(
+            outcome.json::<serde_json::Value>().unwrap(),
+            json!({"a": 1})
+        );
+
+        let failures = outcome.receipt_failures();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].executor_id, "other.near");
+
+        outcome.assert_success();
+        let panic = std::panic::catch_unwind(|| outcome.assert_all_receipts_success()).unwrap_err();
+        assert!(panic
+            .downcast_ref::<String>()
+            .unwrap()
+            .contains("failed on other.near"));
+    }
+
+    #[test]
+    fn extracts_events() {
+        let success = json!({"SuccessValue": ""});
+        let outcome = transaction_outcome(vec![
+            outcome(
+                "token.near",
+                success.clone(),
+                &[
+                    "Transfer 10 from alice.near to bob.near",
+                    r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"alice.near","new_owner_id":"bob.near","amount":"10"}]}"#,
+                    "EVENT_JSON:not a json",
+                    r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":{"amount":"10"}}"#,
+                ],
+            ),
+            outcome(
+                "game.near",
+                success,
+                &[
+                    r#"EVENT_JSON:{"standard":"game","version":"1.0.0","event":"score","data":{"points":7}}"#,
+                ],
+            ),
+        ]);
+
+        assert_eq!(outcome.events().len(), 3);
+
+        let ft_events = outcome.ft_events();
+        assert_eq!(ft_events.len(), 1);
+        assert_eq!(ft_events[0].contract_id, "token.near");
+        assert!(matches!(
+            &ft_events[0].data,
+            Nep141Event::FtTransfer(transfers) if transfers[0].amount.0 == 10 && transfers[0].memo.is_none()
+        ));
+        assert!(outcome.nft_events().is_empty());
+
+        #[derive(serde::Deserialize)]
+        struct Score {
+            points: u32,
+        }
+        let scores = outcome.events_of::<Score>("game", "score").unwrap();
+        assert_eq!(scores.len(), 1);
+        assert_eq!(scores[0].data.points, 7);
+        assert_eq!(scores[0].contract_id, "game.near");
+    }
+}
@@ -1,7 +1,7 @@
 // Source: <https://github.com/near/near-workspaces-rs/blob/10a6c1a00b2b6c937242043312455e05f0d4a125/workspaces/src/types/mod.rs#L513C1-L537C2>
 
 use crate::types::CryptoHash;
-use near_primitives::types::{BlockHeight, EpochId};
+use near_primitives::types::{BlockHeight, EpochId, ShardId};
 
 /// Finality of a transaction or block in which transaction is included in. For more info
 /// go to the [NEAR finality](https://docs.near.org/docs/concepts/transaction#finality) docs.
@@ -65,3 +65,63 @@ impl From<EpochReference> for near_primitives::types::EpochReference {
         }
     }
 }
+
+/// Reference to a chunk, by its hash or by the block and the shard it's included in.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum ChunkReference {
+    /// Reference to a specific chunk hash.
+    AtChunkHash(CryptoHash),
+    /// Reference to the chunk of the shard in a specific block.
+    AtBlock(BlockHeight, ShardId),
+    /// Reference to the chunk of the shard in a specific block hash.
+    AtBlockHash(CryptoHash, ShardId),
+}
+
+impl From<ChunkReference> for near_jsonrpc_client::methods::chunk::ChunkReference {
+    fn from(value: ChunkReference) -> Self {
+        match value {
+            ChunkReference::AtChunkHash(chunk_hash) => Self::ChunkHash {
+                chunk_id: chunk_hash.into(),
+            },
+            ChunkReference::AtBlock(block_height, shard_id) => Self::BlockShardId {
+                block_id: near_primitives::types::BlockId::Height(block_height),
+                shard_id,
+            },
+            ChunkReference::AtBlockHash(block_hash, shard_id) => Self::BlockShardId {
+                block_id: near_primitives::types::BlockId::Hash(block_hash.into()),
+                shard_id,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_jsonrpc_client::methods::chunk;
+    use near_primitives::types::BlockId;
+
+    use super::*;
+
+    #[test]
+    fn chunk_reference_converts_to_rpc() {
+        let hash = CryptoHash([1; 32]);
+        let rpc_hash = near_primitives::hash::CryptoHash::from(hash);
+        assert!(matches!(
+            ChunkReference::AtChunkHash(hash).into(),
+            chunk::ChunkReference::ChunkHash { chunk_id } if chunk_id == rpc_hash
+        ));
+        assert!(matches!(
+            ChunkReference::AtBlock(10, 2).into(),
+            chunk::ChunkReference::BlockShardId {
+                block_id: BlockId::Height(10),
+                shard_id: 2
+            }
+        ));
+        assert!(matches!(
+            ChunkReference::AtBlockHash(hash, 3).into(),
+            chunk::ChunkReference::BlockShardId { block_id: BlockId::Hash(block_hash), shard_id: 3 }
+                if block_hash == rpc_hash
+        ));
+    }
+}
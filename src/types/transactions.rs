@@ -1,9 +1,63 @@
-use near_primitives::{action::Action, types::AccountId};
+use near_jsonrpc_client::methods::tx::RpcTransactionStatusRequest;
+use near_primitives::{action::Action, types::AccountId, views::TxExecutionStatus};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    common::query::{RpcTransactionStatusHandler, TransactionStatusQueryBuilder},
+    errors::QueryError,
+    types::{outcome::TransactionOutcome, CryptoHash},
+    NetworkConfig, Transaction,
+};
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PrepopulateTransaction {
     pub signer_id: AccountId,
     pub receiver_id: AccountId,
     pub actions: Vec<Action>,
 }
+
+/// Execution status of the transaction, as returned by [Transaction::status].
+#[derive(Debug, Clone)]
+pub struct TransactionStatus<T = TransactionOutcome> {
+    /// Execution status the transaction has reached by the time the query returned.
+    pub status: TxExecutionStatus,
+    /// Outcome of the transaction. It's `None` on the statuses below [TxExecutionStatus::ExecutedOptimistic],
+    /// as the transaction isn't executed yet.
+    pub outcome: Option<T>,
+}
+
+/// Handle of the broadcast transaction, to wait for its execution later.
+#[derive(Debug, Clone)]
+pub struct PendingTransaction {
+    pub hash: CryptoHash,
+    pub sender_id: AccountId,
+    /// Execution status the transaction has reached by the time the broadcast returned.
+    pub status: TxExecutionStatus,
+    /// Outcome of the transaction, if it was executed by the time the broadcast returned.
+    pub outcome: Option<TransactionOutcome>,
+}
+
+impl PendingTransaction {
+    /// Queries the transaction, waiting until it reaches the status.
+    pub fn wait_until(
+        &self,
+        status: TxExecutionStatus,
+    ) -> TransactionStatusQueryBuilder<RpcTransactionStatusHandler> {
+        Transaction::status(self.hash, self.sender_id.clone()).wait_until(status)
+    }
+
+    /// Waits until the transaction and all its receipts are executed and final.
+    #[allow(clippy::result_large_err)]
+    pub async fn wait_for_final(
+        &self,
+        network: &NetworkConfig,
+    ) -> Result<TransactionOutcome, QueryError<RpcTransactionStatusRequest>> {
+        let status = self
+            .wait_until(TxExecutionStatus::Final)
+            .fetch_from(network)
+            .await?;
+        status
+            .outcome
+            .ok_or(QueryError::MissingTransactionOutcome(status.status))
+    }
+}
@@ -99,7 +99,7 @@ async fn multiple_tx_at_same_time_from_different_keys() {
     let mut hash_map = HashMap::new();
     for tx in txs {
         tx.assert_success();
-        let public_key = tx.transaction.public_key;
+        let public_key = tx.into_view().transaction.public_key;
         let count = hash_map.entry(public_key).or_insert(0);
         *count += 1;
     }